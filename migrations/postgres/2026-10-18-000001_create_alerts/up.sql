@@ -0,0 +1,9 @@
+-- Your SQL goes here
+CREATE TABLE alerts(
+   id SERIAL PRIMARY KEY,
+   ticker VARCHAR NOT NULL,
+   alert_type VARCHAR NOT NULL,
+   candle_timestamp BIGINT NOT NULL,
+   fired_at BIGINT NOT NULL,
+   created_at TIMESTAMPTZ DEFAULT now()
+)
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX stocks_ticker_timeframe_timestamp;
+ALTER TABLE stocks DROP COLUMN timeframe;
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+-- Rows written before this migration came from the live monitor's default 15 minute candles
+ALTER TABLE stocks ADD COLUMN timeframe INTEGER NOT NULL DEFAULT 15;
+ALTER TABLE stocks ALTER COLUMN timeframe DROP DEFAULT;
+
+-- Keep the first copy of any candle that was saved twice
+DELETE FROM stocks duplicate
+USING stocks original
+WHERE duplicate.ticker = original.ticker
+  AND duplicate.timeframe = original.timeframe
+  AND duplicate.timestamp = original.timestamp
+  AND duplicate.id > original.id;
+
+CREATE UNIQUE INDEX stocks_ticker_timeframe_timestamp ON stocks (ticker, timeframe, timestamp);
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE stocks
+   DROP COLUMN volume,
+   DROP COLUMN ema_20,
+   DROP COLUMN vwap,
+   DROP COLUMN trade_count,
+   DROP COLUMN session;
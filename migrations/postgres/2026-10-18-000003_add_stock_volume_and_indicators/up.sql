@@ -0,0 +1,8 @@
+-- Your SQL goes here
+-- Rows saved before this migration did not record these, volume and ema_20 read back as 0
+ALTER TABLE stocks
+   ADD COLUMN volume BIGINT NOT NULL DEFAULT 0,
+   ADD COLUMN ema_20 FLOAT NOT NULL DEFAULT 0,
+   ADD COLUMN vwap FLOAT,
+   ADD COLUMN trade_count INTEGER,
+   ADD COLUMN session VARCHAR NOT NULL DEFAULT 'regular';
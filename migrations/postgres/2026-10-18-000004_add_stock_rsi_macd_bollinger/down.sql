@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE stocks
+   DROP COLUMN rsi_14,
+   DROP COLUMN macd,
+   DROP COLUMN macd_signal,
+   DROP COLUMN macd_histogram,
+   DROP COLUMN bollinger_middle,
+   DROP COLUMN bollinger_upper,
+   DROP COLUMN bollinger_lower;
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- Null while the indicator is still warming up, and for rows saved before this migration
+ALTER TABLE stocks
+   ADD COLUMN rsi_14 FLOAT,
+   ADD COLUMN macd FLOAT,
+   ADD COLUMN macd_signal FLOAT,
+   ADD COLUMN macd_histogram FLOAT,
+   ADD COLUMN bollinger_middle FLOAT,
+   ADD COLUMN bollinger_upper FLOAT,
+   ADD COLUMN bollinger_lower FLOAT;
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+UPDATE stocks
+SET ema_9 = COALESCE(ema_9, 0), ema_20 = COALESCE(ema_20, 0)
+WHERE ema_9 IS NULL OR ema_20 IS NULL;
+
+ALTER TABLE stocks
+   ALTER COLUMN ema_9 SET NOT NULL,
+   ALTER COLUMN ema_20 SET NOT NULL,
+   ALTER COLUMN ema_20 SET DEFAULT 0;
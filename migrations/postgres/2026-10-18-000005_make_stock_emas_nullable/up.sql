@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- An EMA still warming up used to be saved as 0, it is NULL from now on
+ALTER TABLE stocks
+   ALTER COLUMN ema_9 DROP NOT NULL,
+   ALTER COLUMN ema_20 DROP NOT NULL,
+   ALTER COLUMN ema_20 DROP DEFAULT;
+
+UPDATE stocks
+SET ema_9 = NULLIF(ema_9, 0), ema_20 = NULLIF(ema_20, 0)
+WHERE ema_9 = 0 OR ema_20 = 0;
@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE alerts
+   DROP COLUMN ema_9,
+   DROP COLUMN ema_20,
+   DROP COLUMN rsi_14,
+   DROP COLUMN macd,
+   DROP COLUMN macd_signal,
+   DROP COLUMN macd_histogram,
+   DROP COLUMN bollinger_middle,
+   DROP COLUMN bollinger_upper,
+   DROP COLUMN bollinger_lower;
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+-- The indicators of the candle that fired the alert, null while warming up and for alerts saved before this migration
+ALTER TABLE alerts
+   ADD COLUMN ema_9 FLOAT,
+   ADD COLUMN ema_20 FLOAT,
+   ADD COLUMN rsi_14 FLOAT,
+   ADD COLUMN macd FLOAT,
+   ADD COLUMN macd_signal FLOAT,
+   ADD COLUMN macd_histogram FLOAT,
+   ADD COLUMN bollinger_middle FLOAT,
+   ADD COLUMN bollinger_upper FLOAT,
+   ADD COLUMN bollinger_lower FLOAT;
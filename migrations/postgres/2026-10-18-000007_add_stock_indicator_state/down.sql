@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE stocks
+   DROP COLUMN rsi_average_gain,
+   DROP COLUMN rsi_average_loss,
+   DROP COLUMN macd_fast_ema,
+   DROP COLUMN macd_slow_ema;
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- Running state RSI and MACD continue from after a restart, null while warming up and for rows saved before this migration
+ALTER TABLE stocks
+   ADD COLUMN rsi_average_gain FLOAT,
+   ADD COLUMN rsi_average_loss FLOAT,
+   ADD COLUMN macd_fast_ema FLOAT,
+   ADD COLUMN macd_slow_ema FLOAT;
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE TABLE stocks(
+   id INTEGER PRIMARY KEY AUTOINCREMENT,
+   ticker VARCHAR NOT NULL,
+   open DOUBLE NOT NULL,
+   close DOUBLE NOT NULL,
+   high DOUBLE NOT NULL,
+   low DOUBLE NOT NULL,
+   ema_9 DOUBLE NOT NULL,
+   timestamp BIGINT NOT NULL,
+   created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+)
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+CREATE TABLE alerts(
+   id INTEGER PRIMARY KEY AUTOINCREMENT,
+   ticker VARCHAR NOT NULL,
+   alert_type VARCHAR NOT NULL,
+   candle_timestamp BIGINT NOT NULL,
+   fired_at BIGINT NOT NULL,
+   created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+)
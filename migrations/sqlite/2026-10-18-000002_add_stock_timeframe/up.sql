@@ -0,0 +1,15 @@
+-- Your SQL goes here
+-- Rows written before this migration came from the live monitor's default 15 minute candles
+ALTER TABLE stocks ADD COLUMN timeframe INTEGER NOT NULL DEFAULT 15;
+
+-- Keep the first copy of any candle that was saved twice
+DELETE FROM stocks
+WHERE EXISTS (
+  SELECT 1 FROM stocks original
+  WHERE original.ticker = stocks.ticker
+    AND original.timeframe = stocks.timeframe
+    AND original.timestamp = stocks.timestamp
+    AND original.id < stocks.id
+);
+
+CREATE UNIQUE INDEX stocks_ticker_timeframe_timestamp ON stocks (ticker, timeframe, timestamp);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE stocks DROP COLUMN volume;
+ALTER TABLE stocks DROP COLUMN ema_20;
+ALTER TABLE stocks DROP COLUMN vwap;
+ALTER TABLE stocks DROP COLUMN trade_count;
+ALTER TABLE stocks DROP COLUMN session;
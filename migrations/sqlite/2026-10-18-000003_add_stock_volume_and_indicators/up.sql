@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- Rows saved before this migration did not record these, volume and ema_20 read back as 0
+ALTER TABLE stocks ADD COLUMN volume BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE stocks ADD COLUMN ema_20 DOUBLE NOT NULL DEFAULT 0;
+ALTER TABLE stocks ADD COLUMN vwap DOUBLE;
+ALTER TABLE stocks ADD COLUMN trade_count INTEGER;
+ALTER TABLE stocks ADD COLUMN session VARCHAR NOT NULL DEFAULT 'regular';
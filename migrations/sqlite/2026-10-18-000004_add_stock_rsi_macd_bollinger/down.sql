@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE stocks DROP COLUMN rsi_14;
+ALTER TABLE stocks DROP COLUMN macd;
+ALTER TABLE stocks DROP COLUMN macd_signal;
+ALTER TABLE stocks DROP COLUMN macd_histogram;
+ALTER TABLE stocks DROP COLUMN bollinger_middle;
+ALTER TABLE stocks DROP COLUMN bollinger_upper;
+ALTER TABLE stocks DROP COLUMN bollinger_lower;
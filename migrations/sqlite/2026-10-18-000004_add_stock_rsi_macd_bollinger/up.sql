@@ -0,0 +1,9 @@
+-- Your SQL goes here
+-- Null while the indicator is still warming up, and for rows saved before this migration
+ALTER TABLE stocks ADD COLUMN rsi_14 DOUBLE;
+ALTER TABLE stocks ADD COLUMN macd DOUBLE;
+ALTER TABLE stocks ADD COLUMN macd_signal DOUBLE;
+ALTER TABLE stocks ADD COLUMN macd_histogram DOUBLE;
+ALTER TABLE stocks ADD COLUMN bollinger_middle DOUBLE;
+ALTER TABLE stocks ADD COLUMN bollinger_upper DOUBLE;
+ALTER TABLE stocks ADD COLUMN bollinger_lower DOUBLE;
@@ -0,0 +1,35 @@
+-- Your SQL goes here
+-- An EMA still warming up used to be saved as 0, it is NULL from now on.
+-- SQLite cannot drop NOT NULL from a column, so the table is rebuilt.
+CREATE TABLE stocks_rebuilt(
+   id INTEGER PRIMARY KEY AUTOINCREMENT,
+   ticker VARCHAR NOT NULL,
+   open DOUBLE NOT NULL,
+   close DOUBLE NOT NULL,
+   high DOUBLE NOT NULL,
+   low DOUBLE NOT NULL,
+   ema_9 DOUBLE,
+   timestamp BIGINT NOT NULL,
+   created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+   timeframe INTEGER NOT NULL,
+   volume BIGINT NOT NULL DEFAULT 0,
+   ema_20 DOUBLE,
+   vwap DOUBLE,
+   trade_count INTEGER,
+   session VARCHAR NOT NULL DEFAULT 'regular',
+   rsi_14 DOUBLE,
+   macd DOUBLE,
+   macd_signal DOUBLE,
+   macd_histogram DOUBLE,
+   bollinger_middle DOUBLE,
+   bollinger_upper DOUBLE,
+   bollinger_lower DOUBLE
+);
+
+INSERT INTO stocks_rebuilt (id, ticker, open, close, high, low, ema_9, timestamp, created_at, timeframe, volume, ema_20, vwap, trade_count, session, rsi_14, macd, macd_signal, macd_histogram, bollinger_middle, bollinger_upper, bollinger_lower)
+SELECT id, ticker, open, close, high, low, NULLIF(ema_9, 0), timestamp, created_at, timeframe, volume, NULLIF(ema_20, 0), vwap, trade_count, session, rsi_14, macd, macd_signal, macd_histogram, bollinger_middle, bollinger_upper, bollinger_lower
+FROM stocks;
+
+DROP TABLE stocks;
+ALTER TABLE stocks_rebuilt RENAME TO stocks;
+CREATE UNIQUE INDEX stocks_ticker_timeframe_timestamp ON stocks (ticker, timeframe, timestamp);
@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE alerts DROP COLUMN ema_9;
+ALTER TABLE alerts DROP COLUMN ema_20;
+ALTER TABLE alerts DROP COLUMN rsi_14;
+ALTER TABLE alerts DROP COLUMN macd;
+ALTER TABLE alerts DROP COLUMN macd_signal;
+ALTER TABLE alerts DROP COLUMN macd_histogram;
+ALTER TABLE alerts DROP COLUMN bollinger_middle;
+ALTER TABLE alerts DROP COLUMN bollinger_upper;
+ALTER TABLE alerts DROP COLUMN bollinger_lower;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- The indicators of the candle that fired the alert, null while warming up and for alerts saved before this migration
+ALTER TABLE alerts ADD COLUMN ema_9 DOUBLE;
+ALTER TABLE alerts ADD COLUMN ema_20 DOUBLE;
+ALTER TABLE alerts ADD COLUMN rsi_14 DOUBLE;
+ALTER TABLE alerts ADD COLUMN macd DOUBLE;
+ALTER TABLE alerts ADD COLUMN macd_signal DOUBLE;
+ALTER TABLE alerts ADD COLUMN macd_histogram DOUBLE;
+ALTER TABLE alerts ADD COLUMN bollinger_middle DOUBLE;
+ALTER TABLE alerts ADD COLUMN bollinger_upper DOUBLE;
+ALTER TABLE alerts ADD COLUMN bollinger_lower DOUBLE;
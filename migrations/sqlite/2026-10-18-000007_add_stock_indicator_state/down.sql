@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE stocks DROP COLUMN rsi_average_gain;
+ALTER TABLE stocks DROP COLUMN rsi_average_loss;
+ALTER TABLE stocks DROP COLUMN macd_fast_ema;
+ALTER TABLE stocks DROP COLUMN macd_slow_ema;
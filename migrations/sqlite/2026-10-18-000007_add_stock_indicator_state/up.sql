@@ -0,0 +1,6 @@
+-- Your SQL goes here
+-- Running state RSI and MACD continue from after a restart, null while warming up and for rows saved before this migration
+ALTER TABLE stocks ADD COLUMN rsi_average_gain DOUBLE;
+ALTER TABLE stocks ADD COLUMN rsi_average_loss DOUBLE;
+ALTER TABLE stocks ADD COLUMN macd_fast_ema DOUBLE;
+ALTER TABLE stocks ADD COLUMN macd_slow_ema DOUBLE;
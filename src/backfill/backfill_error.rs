@@ -0,0 +1,46 @@
+use std::{error::Error, fmt};
+
+use crate::market_data::{fetch_error::FetchError, select_provider::ProviderSetupError};
+
+/**
+ * Why a backfill stopped
+ *
+ * InvalidRange: the last day is before the first
+ * ProviderSetup: POLYGON_API_KEY is not set
+ * Fetch: Polygon failed to return a chunk of a ticker's history
+ * Database: candles could not be written to the stocks table
+ */
+#[derive(Debug)]
+pub enum BackfillError {
+    InvalidRange(String),
+    ProviderSetup(ProviderSetupError),
+    Fetch { ticker: String, source: FetchError },
+    Database(String),
+}
+
+impl fmt::Display for BackfillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackfillError::InvalidRange(message) => write!(f, "invalid date range: {}", message),
+            BackfillError::ProviderSetup(e) => write!(f, "{}", e),
+            BackfillError::Fetch { ticker, source } => write!(f, "could not fetch history for {}: {}", ticker, source),
+            BackfillError::Database(message) => write!(f, "could not save candles: {}", message),
+        }
+    }
+}
+
+impl Error for BackfillError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BackfillError::ProviderSetup(e) => Some(e),
+            BackfillError::Fetch { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<ProviderSetupError> for BackfillError {
+    fn from(e: ProviderSetupError) -> Self {
+        BackfillError::ProviderSetup(e)
+    }
+}
@@ -0,0 +1,2 @@
+pub mod backfill_error;
+pub mod run_backfill;
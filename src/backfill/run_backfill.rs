@@ -0,0 +1,128 @@
+use std::slice;
+
+use chrono::TimeDelta;
+
+use crate::{
+    backfill::backfill_error::BackfillError,
+    cli::BackfillArgs,
+    config::monitor_config::MonitorSettings,
+    data_polling::flush_stock_data::unsaved_stock_rows,
+    database::{
+        candle_repository::CandleRepository,
+        connection_pool::connect_database,
+        models::NewStock,
+    },
+    market_data::{fetch_error::FetchError, select_provider::select_polygon_provider},
+    polygon_api::{stock::StockData, stock_data_response::PriceDatum},
+    replay::run_replay::{new_york_midnight, replay_tickers},
+};
+
+/**
+ * Loads every requested ticker's candles for the date range from Polygon into the stocks table
+ *
+ * Candles go through StockData the same way live polls do, so the saved EMAs and the sessions
+ * kept match what the monitor would have written. Each chunk of days is saved before the next
+ * is fetched, and rows are upserted, so an interrupted backfill can simply be run again.
+ */
+pub async fn run_backfill(settings: &MonitorSettings, args: &BackfillArgs) -> Result<(), BackfillError> {
+    if args.to < args.from {
+        return Err(BackfillError::InvalidRange(format!("--to {} is before --from {}", args.to, args.from)));
+    }
+
+    let start = new_york_midnight(args.from);
+    let end = new_york_midnight(args.to.succ_opt().unwrap_or(args.to));
+    let chunk = TimeDelta::days(args.chunk_days as i64);
+    let provider = select_polygon_provider(&settings.provider)?;
+    let candles = connect_database().await.map_err(|e| BackfillError::Database(e.to_string()))?.candles;
+
+    for ticker in replay_tickers(settings, &args.tickers) {
+        let mut stock_data = StockData::new(ticker.clone());
+        let mut fetched = 0;
+        let mut saved = 0;
+
+        let mut chunk_start = start;
+        while chunk_start < end {
+            let chunk_end = (chunk_start + chunk).min(end);
+            let bars = match provider
+                .fetch_bars(&ticker.symbol, ticker.timeframe, chunk_start.timestamp_millis(), chunk_end.timestamp_millis() - 1)
+                .await
+            {
+                Ok(bars) => bars,
+                Err(FetchError::EmptyResults) => Vec::new(),
+                Err(source) => return Err(BackfillError::Fetch { ticker: ticker.symbol.clone(), source }),
+            };
+            fetched += bars.len();
+
+            saved += save_stock_rows(candles.as_ref(), stock_rows(&mut stock_data, &bars)).await?;
+            println!("{}: saved candles up to {}", ticker.symbol, chunk_end);
+            chunk_start = chunk_end;
+        }
+
+        println!("{}: fetched {} candles, saved {}", ticker.symbol, fetched, saved);
+    }
+
+    Ok(())
+}
+
+/**
+ * Adds bars to the ticker's StockData one at a time and collects a row for each one it keeps,
+ * before the queue can evict it
+ *
+ * @param: bars: oldest first
+ */
+fn stock_rows(stock_data: &mut StockData<'_>, bars: &[PriceDatum]) -> Vec<NewStock> {
+    let mut rows = Vec::with_capacity(bars.len());
+
+    for bar in bars {
+        if stock_data.add_new_stock_data(slice::from_ref(bar)) == 0 {
+            continue;
+        }
+        rows.extend(unsaved_stock_rows(stock_data));
+        stock_data.persisted_until = stock_data.last_timestamp();
+    }
+
+    rows
+}
+
+async fn save_stock_rows(candles: &dyn CandleRepository, rows: Vec<NewStock>) -> Result<usize, BackfillError> {
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    candles
+        .save_candles(rows)
+        .await
+        .map_err(|e| BackfillError::Database(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::{
+        config::monitor_config::TickerConfig,
+        test_fixtures::{bar, ticker_config},
+    };
+
+    use super::*;
+
+    const FIFTEEN_MINUTES: u64 = 15 * 60 * 1000;
+
+    #[test]
+    fn keeps_a_row_for_every_candle_even_past_the_queue_capacity() {
+        let mut stock_data = StockData::new(TickerConfig { queue_capacity: 2, ..ticker_config("QQQ") });
+
+        // Tuesday 15 July 2025: a pre-market candle, then the first five regular candles, one of them twice
+        let open = Utc.with_ymd_and_hms(2025, 7, 15, 13, 30, 0).unwrap().timestamp_millis() as u64;
+        let mut bars = vec![bar(open - FIFTEEN_MINUTES, 99.0)];
+        bars.extend((0..5).map(|idx| bar(open + idx * FIFTEEN_MINUTES, 100.0 + idx as f64)));
+        bars.insert(3, bars[2].clone());
+
+        let rows = stock_rows(&mut stock_data, &bars);
+
+        let saved: Vec<u64> = rows.iter().map(|row| row.timestamp as u64).collect();
+        assert_eq!(saved, (0..5).map(|idx| open + idx * FIFTEEN_MINUTES).collect::<Vec<_>>());
+        assert!(rows.iter().all(|row| row.ticker == "QQQ" && row.timeframe == 15));
+        assert_eq!(stock_data.stock_data.len(), 2);
+    }
+}
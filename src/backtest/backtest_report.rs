@@ -0,0 +1,122 @@
+use std::fmt;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{
+    backtest::forward_returns::ReturnDistribution,
+    cli::HistorySource,
+    replay::replay_report::TickerReplay,
+};
+
+/**
+ * How every alert type would have performed over a backtest
+ *
+ * @horizons: bar counts forward returns were measured at
+ * @hit_bars, @min_move_pct: an alert is a hit when the close hit_bars later moved at least min_move_pct either way
+ * @tickers: bars and alerts per backtested ticker
+ * @alert_types: one entry per alert type that fired
+ */
+#[derive(Debug, Serialize)]
+pub struct BacktestReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub source: HistorySource,
+    pub horizons: Vec<u32>,
+    pub hit_bars: u32,
+    pub min_move_pct: f64,
+    pub tickers: Vec<TickerReplay>,
+    pub alert_types: Vec<AlertTypeReport>,
+}
+
+/**
+ * @fired: how many times the alert fired
+ * @forward_returns: one distribution per horizon
+ * @false_positives: all alerts of this type, then split by ticker and by MarketTimePeriod
+ */
+#[derive(Debug, Serialize)]
+pub struct AlertTypeReport {
+    pub alert_type: String,
+    pub fired: usize,
+    pub forward_returns: Vec<ReturnDistribution>,
+    pub false_positives: FalsePositiveRate,
+    pub by_ticker: Vec<FalsePositiveRate>,
+    pub by_period: Vec<FalsePositiveRate>,
+}
+
+/**
+ * @key: the ticker or period the alerts were grouped by, "all" for the whole alert type
+ * @scored: alerts with enough history after them to be judged
+ * @rate: false_positives over scored, None when nothing could be scored
+ */
+#[derive(Debug, Serialize)]
+pub struct FalsePositiveRate {
+    pub key: String,
+    pub fired: usize,
+    pub scored: usize,
+    pub false_positives: usize,
+    pub rate: Option<f64>,
+}
+
+/**
+ * Tables for the terminal, the JSON form has every field
+ */
+impl fmt::Display for BacktestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Backtest {} to {} from {:?}", self.from, self.to, self.source)?;
+        writeln!(
+            f,
+            "An alert is a false positive when the close {} bars later moved less than {:.2}%",
+            self.hit_bars, self.min_move_pct
+        )?;
+        let bars: usize = self.tickers.iter().map(|ticker| ticker.bars).sum();
+        write!(f, "{} bars across {} tickers", bars, self.tickers.len())?;
+
+        if self.alert_types.is_empty() {
+            return write!(f, "\nNo alerts fired");
+        }
+
+        for alert_type in &self.alert_types {
+            writeln!(f)?;
+            writeln!(f)?;
+            writeln!(f, "{}: fired {} times", alert_type.alert_type, alert_type.fired)?;
+            writeln!(
+                f,
+                "{:>6} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>6}",
+                "bars", "samples", "mean%", "p10%", "median%", "p90%", "max%", "up"
+            )?;
+            for distribution in &alert_type.forward_returns {
+                match &distribution.stats {
+                    Some(stats) => writeln!(
+                        f,
+                        "{:>6} {:>8} {:>8.2} {:>8.2} {:>8.2} {:>8.2} {:>8.2} {:>5.0}%",
+                        distribution.horizon_bars,
+                        distribution.samples,
+                        stats.mean,
+                        stats.p10,
+                        stats.median,
+                        stats.p90,
+                        stats.max,
+                        stats.positive_rate * 100.0
+                    )?,
+                    None => writeln!(f, "{:>6} {:>8}", distribution.horizon_bars, distribution.samples)?,
+                }
+            }
+
+            write!(f, "{:<12} {:>7} {:>7} {:>7} {:>7}", "false pos.", "fired", "scored", "false", "rate")?;
+            let rates = std::iter::once(&alert_type.false_positives)
+                .chain(&alert_type.by_ticker)
+                .chain(&alert_type.by_period);
+            for rate in rates {
+                let shown = rate.rate.map(|rate| format!("{:.1}%", rate * 100.0)).unwrap_or_else(|| "-".to_string());
+                write!(
+                    f,
+                    "\n{:<12} {:>7} {:>7} {:>7} {:>7}",
+                    rate.key, rate.fired, rate.scored, rate.false_positives, shown
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
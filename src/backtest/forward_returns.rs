@@ -0,0 +1,122 @@
+use serde::Serialize;
+
+use crate::polygon_api::stock_data_response::PriceDatum;
+
+/**
+ * Percent change from the close of the candle starting at candle_timestamp to the close horizon bars later
+ *
+ * @param: bars: one ticker's history, oldest first
+ * @return None when the candle is not in the history, the history ends too soon, or the base close is zero
+ */
+pub fn forward_return_pct(bars: &[PriceDatum], candle_timestamp: u64, horizon: u32) -> Option<f64> {
+    let idx = bars.binary_search_by_key(&candle_timestamp, |bar| bar.timestamp).ok()?;
+    let base = bars[idx].close;
+    let later = bars.get(idx + horizon as usize)?.close;
+    if base == 0.0 {
+        return None;
+    }
+    return Some((later / base - 1.0) * 100.0);
+}
+
+/**
+ * Spread of the forward returns measured at one horizon
+ *
+ * @samples: alerts with enough history after them to measure
+ * @stats: None when there were no samples
+ */
+#[derive(Debug, Serialize)]
+pub struct ReturnDistribution {
+    pub horizon_bars: u32,
+    pub samples: usize,
+    pub stats: Option<ReturnStats>,
+}
+
+/**
+ * All values in percent, except positive_rate which is the share of returns above zero
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ReturnStats {
+    pub mean: f64,
+    pub min: f64,
+    pub p10: f64,
+    pub p25: f64,
+    pub median: f64,
+    pub p75: f64,
+    pub p90: f64,
+    pub max: f64,
+    pub positive_rate: f64,
+}
+
+impl ReturnDistribution {
+    pub fn from_returns(horizon_bars: u32, mut returns: Vec<f64>) -> Self {
+        returns.sort_by(f64::total_cmp);
+        let stats = if returns.is_empty() {
+            None
+        } else {
+            let count = returns.len() as f64;
+            Some(ReturnStats {
+                mean: returns.iter().sum::<f64>() / count,
+                min: returns[0],
+                p10: percentile(&returns, 10.0),
+                p25: percentile(&returns, 25.0),
+                median: percentile(&returns, 50.0),
+                p75: percentile(&returns, 75.0),
+                p90: percentile(&returns, 90.0),
+                max: returns[returns.len() - 1],
+                positive_rate: returns.iter().filter(|value| **value > 0.0).count() as f64 / count,
+            })
+        };
+
+        return ReturnDistribution { horizon_bars, samples: returns.len(), stats };
+    }
+}
+
+/**
+ * Linear interpolation between the closest ranks
+ *
+ * @param: sorted: ascending, not empty
+ * @param: pct: 0 to 100
+ */
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    let rank = pct / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    return sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_fixtures::bar;
+
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn measures_the_close_horizon_bars_later() {
+        let bars = vec![bar(0, 100.0), bar(1, 101.0), bar(2, 98.0)];
+
+        assert_close(forward_return_pct(&bars, 0, 1).unwrap(), 1.0);
+        assert_close(forward_return_pct(&bars, 0, 2).unwrap(), -2.0);
+        assert_eq!(forward_return_pct(&bars, 1, 2), None);
+        assert_eq!(forward_return_pct(&bars, 5, 1), None);
+    }
+
+    #[test]
+    fn summarises_returns() {
+        let distribution = ReturnDistribution::from_returns(4, vec![3.0, -1.0, 1.0, 2.0, 0.0]);
+        let stats = distribution.stats.unwrap();
+
+        assert_eq!(distribution.samples, 5);
+        assert_eq!(stats.mean, 1.0);
+        assert_eq!(stats.min, -1.0);
+        assert_eq!(stats.median, 1.0);
+        assert_eq!(stats.p25, 0.0);
+        assert_close(stats.p90, 2.6);
+        assert_eq!(stats.max, 3.0);
+        assert_eq!(stats.positive_rate, 0.6);
+        assert!(ReturnDistribution::from_returns(1, Vec::new()).stats.is_none());
+    }
+}
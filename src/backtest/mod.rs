@@ -0,0 +1,3 @@
+pub mod backtest_report;
+pub mod forward_returns;
+pub mod run_backtest;
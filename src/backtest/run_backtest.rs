@@ -0,0 +1,230 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::DateTime;
+
+use crate::{
+    backtest::{
+        backtest_report::{AlertTypeReport, BacktestReport, FalsePositiveRate},
+        forward_returns::{forward_return_pct, ReturnDistribution},
+    },
+    cli::BacktestArgs,
+    config::monitor_config::{MonitorSettings, TickerConfig},
+    data_polling::alert_cluster::AlertRecord,
+    market_calendar::calendar::MarketCalendar,
+    polygon_api::{stock::MarketTimePeriod, stock_data_response::PriceDatum},
+    replay::{
+        load_history::load_history,
+        replay_error::ReplayError,
+        run_replay::{new_york_midnight, replay_history, replay_tickers},
+    },
+};
+
+/**
+ * Replays stored candles through the polling pipeline, then scores every captured alert
+ * against the bars that followed it
+ */
+pub async fn run_backtest(settings: &MonitorSettings, args: &BacktestArgs) -> Result<BacktestReport, ReplayError> {
+    if args.to < args.from {
+        return Err(ReplayError::InvalidRange(format!("--to {} is before --from {}", args.to, args.from)));
+    }
+    if args.min_move_pct.is_nan() || args.min_move_pct < 0.0 {
+        return Err(ReplayError::InvalidArgument(format!("--min-move-pct {} must be zero or more", args.min_move_pct)));
+    }
+
+    let start = new_york_midnight(args.from);
+    let end = new_york_midnight(args.to.succ_opt().unwrap_or(args.to));
+    let tickers = replay_tickers(settings, &args.tickers);
+
+    let timestamp_to = end.timestamp_millis() - 1;
+    let history = load_history(args.source, &settings.provider, &tickers, start.timestamp_millis(), timestamp_to).await?;
+
+    let (ticker_replays, alerts) = replay_history(&tickers, history.clone(), settings.polling, start, end).await;
+
+    let mut horizons = args.horizons.clone();
+    horizons.sort_unstable();
+    horizons.dedup();
+
+    Ok(BacktestReport {
+        from: args.from,
+        to: args.to,
+        source: args.source,
+        alert_types: score_alerts(&tickers, &history, &alerts, &horizons, args.hit_bars, args.min_move_pct),
+        horizons,
+        hit_bars: args.hit_bars,
+        min_move_pct: args.min_move_pct,
+        tickers: ticker_replays,
+    })
+}
+
+/**
+ * One captured alert with what the price did after it
+ *
+ * @period: the part of the regular session the alert candle is in, None outside regular hours
+ * @returns: forward return per horizon, in the same order as the horizons
+ * @hit_return: forward return at hit_bars, None when the history ends too soon
+ */
+struct ScoredAlert<'a> {
+    alert: &'a AlertRecord,
+    period: Option<MarketTimePeriod>,
+    returns: Vec<Option<f64>>,
+    hit_return: Option<f64>,
+}
+
+/**
+ * Groups alerts by type and summarises their forward returns and false positive rates
+ *
+ * @param: history: every ticker's bars, oldest first
+ * @return one report per alert type, sorted by name
+ */
+fn score_alerts(
+    tickers: &[TickerConfig],
+    history: &HashMap<String, Vec<PriceDatum>>,
+    alerts: &[AlertRecord],
+    horizons: &[u32],
+    hit_bars: u32,
+    min_move_pct: f64,
+) -> Vec<AlertTypeReport> {
+    let mut by_type: BTreeMap<&str, Vec<ScoredAlert>> = BTreeMap::new();
+
+    for alert in alerts {
+        let bars = history.get(&alert.ticker).map(Vec::as_slice).unwrap_or(&[]);
+        let period = tickers
+            .iter()
+            .find(|ticker| ticker.symbol == alert.ticker)
+            .and_then(|ticker| market_time_period(ticker, alert.candle_timestamp));
+
+        by_type.entry(alert.alert_type.as_str()).or_default().push(ScoredAlert {
+            alert,
+            period,
+            returns: horizons.iter().map(|horizon| forward_return_pct(bars, alert.candle_timestamp, *horizon)).collect(),
+            hit_return: forward_return_pct(bars, alert.candle_timestamp, hit_bars),
+        });
+    }
+
+    by_type
+        .into_iter()
+        .map(|(alert_type, scored)| {
+            let forward_returns = horizons
+                .iter()
+                .enumerate()
+                .map(|(idx, horizon)| {
+                    ReturnDistribution::from_returns(*horizon, scored.iter().filter_map(|alert| alert.returns[idx]).collect())
+                })
+                .collect();
+
+            let mut by_ticker: BTreeMap<String, Vec<&ScoredAlert>> = BTreeMap::new();
+            let mut by_period: BTreeMap<Option<MarketTimePeriod>, Vec<&ScoredAlert>> = BTreeMap::new();
+            for alert in &scored {
+                by_ticker.entry(alert.alert.ticker.clone()).or_default().push(alert);
+                by_period.entry(alert.period).or_default().push(alert);
+            }
+
+            AlertTypeReport {
+                alert_type: alert_type.to_string(),
+                fired: scored.len(),
+                forward_returns,
+                false_positives: false_positive_rate("all".to_string(), &scored.iter().collect::<Vec<_>>(), min_move_pct),
+                by_ticker: by_ticker
+                    .into_iter()
+                    .map(|(ticker, alerts)| false_positive_rate(ticker, &alerts, min_move_pct))
+                    .collect(),
+                by_period: by_period
+                    .into_iter()
+                    .map(|(period, alerts)| {
+                        let key = period.map(|period| format!("{:?}", period)).unwrap_or_else(|| "OTHER".to_string());
+                        false_positive_rate(key, &alerts, min_move_pct)
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/**
+ * An alert is a false positive when the price moved less than min_move_pct either way by hit_bars
+ */
+fn false_positive_rate(key: String, alerts: &[&ScoredAlert], min_move_pct: f64) -> FalsePositiveRate {
+    let hit_returns: Vec<f64> = alerts.iter().filter_map(|alert| alert.hit_return).collect();
+    let false_positives = hit_returns.iter().filter(|pct| pct.abs() < min_move_pct).count();
+
+    FalsePositiveRate {
+        key,
+        fired: alerts.len(),
+        scored: hit_returns.len(),
+        false_positives,
+        rate: (!hit_returns.is_empty()).then(|| false_positives as f64 / hit_returns.len() as f64),
+    }
+}
+
+/**
+ * The MarketTimePeriod of the candle starting at candle_timestamp, on the ticker's own calendar
+ */
+fn market_time_period(ticker: &TickerConfig, candle_timestamp: u64) -> Option<MarketTimePeriod> {
+    let calendar = MarketCalendar::for_asset_class(ticker.asset_class);
+    let candle_start = DateTime::from_timestamp_millis(candle_timestamp as i64)?;
+    let session = calendar.session_on(calendar.local_date(candle_start))?;
+    MarketTimePeriod::for_candle(&session, candle_start)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::{indicators::indicator_set::IndicatorValues, test_fixtures::{bar, ticker_config}};
+
+    use super::*;
+
+    const FIFTEEN_MINUTES: u64 = 15 * 60 * 1000;
+
+    fn alert(ticker: &str, candle_timestamp: u64) -> AlertRecord {
+        AlertRecord {
+            ticker: ticker.to_string(),
+            alert_type: "volume_spike".to_string(),
+            candle_timestamp,
+            fired_at: 0,
+            indicators: IndicatorValues::default(),
+        }
+    }
+
+    // 15 minute bars from first_start with the given closes
+    fn bars(first_start: u64, closes: &[f64]) -> Vec<PriceDatum> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(idx, close)| bar(first_start + idx as u64 * FIFTEEN_MINUTES, *close))
+            .collect()
+    }
+
+    #[test]
+    fn scores_alerts_by_ticker_and_period() {
+        // Tuesday 15 July 2025, opening at 13:30 UTC and closing at 20:00 UTC
+        let open = Utc.with_ymd_and_hms(2025, 7, 15, 13, 30, 0).unwrap().timestamp_millis() as u64;
+        let last_hour = Utc.with_ymd_and_hms(2025, 7, 15, 19, 0, 0).unwrap().timestamp_millis() as u64;
+
+        let history = HashMap::from([
+            ("QQQ".to_string(), bars(open, &[100.0, 101.0, 102.0])),
+            ("NVDA".to_string(), bars(last_hour, &[100.0, 100.1, 100.2])),
+        ]);
+        let alerts = vec![alert("QQQ", open), alert("NVDA", last_hour), alert("NVDA", last_hour + 2 * FIFTEEN_MINUTES)];
+
+        let reports = score_alerts(&[ticker_config("QQQ"), ticker_config("NVDA")], &history, &alerts, &[1, 2], 2, 0.5);
+
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert_eq!(report.fired, 3);
+        assert_eq!(report.forward_returns[0].samples, 2);
+        assert_eq!(report.forward_returns[1].samples, 2);
+
+        // QQQ moved 2% two bars later, NVDA 0.2%, and the last NVDA alert has no bars after it
+        assert_eq!(report.false_positives.scored, 2);
+        assert_eq!(report.false_positives.false_positives, 1);
+        assert_eq!(report.by_ticker.iter().map(|rate| (rate.key.as_str(), rate.rate)).collect::<Vec<_>>(), vec![
+            ("NVDA", Some(1.0)),
+            ("QQQ", Some(0.0))
+        ]);
+        assert_eq!(report.by_period.iter().map(|rate| (rate.key.as_str(), rate.fired)).collect::<Vec<_>>(), vec![
+            ("OPEN", 1),
+            ("CLOSE", 2)
+        ]);
+    }
+}
@@ -0,0 +1,121 @@
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+
+/**
+ * Command line for the monitor, with no subcommand it serves and polls live
+ */
+#[derive(Parser, Debug)]
+#[command(about = "Polls market data and alerts on unusual volume", long_about = None)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the HTTP server and poll the watchlist live (the default)
+    Serve,
+    /// Run the polling pipeline over past bars and report the alerts it would have sent
+    Replay(ReplayArgs),
+    /// Replay months of stored candles and report how each alert type would have performed
+    Backtest(BacktestArgs),
+    /// Load past candles from Polygon into the stocks table
+    Backfill(BackfillArgs),
+}
+
+/**
+ * @from, @to: first and last day replayed, inclusive, as New York dates
+ * @tickers: which tickers to replay, the whole watchlist when empty
+ * @source: where historical bars are read from
+ * @report: file the JSON report is written to
+ */
+#[derive(Args, Debug, Clone)]
+pub struct ReplayArgs {
+    /// First day to replay, e.g. 2025-07-14
+    #[arg(long)]
+    pub from: NaiveDate,
+    /// Last day to replay, inclusive
+    #[arg(long)]
+    pub to: NaiveDate,
+    /// Comma separated tickers, defaults to the watchlist in the config file
+    #[arg(long, value_delimiter = ',')]
+    pub tickers: Vec<String>,
+    /// Where to read historical bars from
+    #[arg(long, value_enum, default_value_t = HistorySource::Provider)]
+    pub source: HistorySource,
+    /// Write the JSON report to this file instead of stdout
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+}
+
+/**
+ * @from, @to: first and last day backtested, inclusive, as New York dates
+ * @tickers: which tickers to backtest, the whole watchlist when empty
+ * @source: where historical bars are read from
+ * @horizons: bar counts after each alert that forward returns are measured at
+ * @hit_bars: the horizon an alert is judged at
+ * @min_move_pct: smallest absolute move, in percent, that makes an alert a hit rather than a false positive
+ * @report: file the JSON report is written to
+ */
+#[derive(Args, Debug, Clone)]
+pub struct BacktestArgs {
+    /// First day to backtest, e.g. 2025-01-02
+    #[arg(long)]
+    pub from: NaiveDate,
+    /// Last day to backtest, inclusive
+    #[arg(long)]
+    pub to: NaiveDate,
+    /// Comma separated tickers, defaults to the watchlist in the config file
+    #[arg(long, value_delimiter = ',')]
+    pub tickers: Vec<String>,
+    /// Where to read historical bars from
+    #[arg(long, value_enum, default_value_t = HistorySource::Provider)]
+    pub source: HistorySource,
+    /// Comma separated bar counts to measure forward returns at
+    #[arg(long, value_delimiter = ',', default_values_t = [1, 4, 8], value_parser = clap::value_parser!(u32).range(1..))]
+    pub horizons: Vec<u32>,
+    /// Bars after the alert at which it is scored as a hit or a false positive
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+    pub hit_bars: u32,
+    /// Smallest absolute move in percent, at --hit-bars, that counts as a hit
+    #[arg(long, default_value_t = 0.5)]
+    pub min_move_pct: f64,
+    /// Write the JSON report to this file instead of stdout
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+}
+
+/**
+ * @from, @to: first and last day loaded, inclusive, as New York dates
+ * @tickers: which tickers to load, the whole watchlist when empty
+ * @chunk_days: how many days each request covers
+ */
+#[derive(Args, Debug, Clone)]
+pub struct BackfillArgs {
+    /// First day to load, e.g. 2025-01-02
+    #[arg(long)]
+    pub from: NaiveDate,
+    /// Last day to load, inclusive
+    #[arg(long)]
+    pub to: NaiveDate,
+    /// Comma separated tickers, defaults to the watchlist in the config file
+    #[arg(long, value_delimiter = ',')]
+    pub tickers: Vec<String>,
+    /// Days of candles requested at a time, each chunk is saved before the next is fetched
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..))]
+    pub chunk_days: u32,
+}
+
+/**
+ * Provider: the market data provider from the config file
+ * Database: candles the monitor saved to the stocks table
+ */
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistorySource {
+    Provider,
+    Database,
+}
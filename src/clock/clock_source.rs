@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+/**
+ * Where the polling loop gets the current time from, and how it waits for a later one
+ *
+ * Production uses the system clock. Testing against delayed data shifts it back, and tests
+ * use a simulated clock so a whole trading day can run without waiting on real time.
+ */
+#[async_trait]
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /**
+     * Resolves once now() has reached deadline, straight away if it already has
+     */
+    async fn sleep_until(&self, deadline: DateTime<Utc>);
+}
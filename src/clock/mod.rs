@@ -0,0 +1,5 @@
+pub mod clock_source;
+pub mod offset_clock;
+pub mod select_clock;
+pub mod simulated_clock;
+pub mod system_clock;
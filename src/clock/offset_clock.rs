@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+use tokio::time::sleep;
+
+use crate::clock::clock_source::Clock;
+
+/**
+ * The wall clock shifted by a fixed amount, time still passes at the normal rate
+ *
+ * Used to run against data plans that are delayed, or to replay a past session live.
+ */
+pub struct OffsetClock {
+    offset: TimeDelta,
+}
+
+impl OffsetClock {
+    /**
+     * @offset: added to the wall clock, negative to look into the past
+     */
+    pub fn new(offset: TimeDelta) -> Self {
+        OffsetClock { offset }
+    }
+
+    /**
+     * A clock that reads start right now and keeps ticking from there
+     */
+    pub fn starting_at(start: DateTime<Utc>) -> Self {
+        Self::new(start - Utc::now())
+    }
+}
+
+#[async_trait]
+impl Clock for OffsetClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now() + self.offset
+    }
+
+    async fn sleep_until(&self, deadline: DateTime<Utc>) {
+        if let Ok(duration) = (deadline - self.now()).to_std() {
+            sleep(duration).await;
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use chrono::TimeDelta;
+
+use crate::{
+    clock::{clock_source::Clock, offset_clock::OffsetClock, system_clock::SystemClock},
+    config::monitor_config::ClockConfig,
+};
+
+/**
+ * Builds the clock described by the [clock] section of the config
+ */
+pub fn select_clock(clock_config: &ClockConfig) -> Arc<dyn Clock> {
+    if let Some(start) = clock_config.start {
+        return Arc::new(OffsetClock::starting_at(start));
+    }
+
+    if clock_config.offset_hours != 0 {
+        return Arc::new(OffsetClock::new(TimeDelta::hours(clock_config.offset_hours)));
+    }
+
+    Arc::new(SystemClock)
+}
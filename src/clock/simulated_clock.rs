@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+use tokio::sync::watch;
+
+use crate::clock::clock_source::Clock;
+
+/**
+ * A clock that only moves when told to
+ *
+ * Manual clocks are moved with advance and set, and sleepers wake once the time passes their deadline.
+ * Auto advancing clocks jump straight to whatever deadline is slept until, so a polling loop
+ * runs through a trading day as fast as it can process each candle.
+ */
+pub struct SimulatedClock {
+    now: watch::Sender<DateTime<Utc>>,
+    auto_advance: bool,
+}
+
+impl SimulatedClock {
+    // Manual clocks are only driven by tests so far, replays use an auto advancing one
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn new(start: DateTime<Utc>) -> Self {
+        SimulatedClock {
+            now: watch::Sender::new(start),
+            auto_advance: false,
+        }
+    }
+
+    pub fn auto_advancing(start: DateTime<Utc>) -> Self {
+        SimulatedClock {
+            now: watch::Sender::new(start),
+            auto_advance: true,
+        }
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn advance(&self, by: TimeDelta) {
+        self.now.send_modify(|now| *now += by);
+    }
+
+    /**
+     * Moves the clock to at, never backwards
+     */
+    pub fn set(&self, at: DateTime<Utc>) {
+        self.now.send_if_modified(|now| {
+            if at <= *now {
+                return false;
+            }
+            *now = at;
+            true
+        });
+    }
+
+    /**
+     * How many tasks are waiting on the clock, so a test moving it by hand knows the code under test is asleep
+     */
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn sleepers(&self) -> usize {
+        self.now.receiver_count()
+    }
+
+    /**
+     * Waits for the clock to reach deadline without moving it, even on an auto advancing clock
+     */
+    pub async fn wait_until(&self, deadline: DateTime<Utc>) {
+        let mut now_rx = self.now.subscribe();
+        // The sender lives as long as self, so this only returns once the deadline is reached
+        let _ = now_rx.wait_for(|now| *now >= deadline).await;
+    }
+}
+
+#[async_trait]
+impl Clock for SimulatedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.borrow()
+    }
+
+    async fn sleep_until(&self, deadline: DateTime<Utc>) {
+        if self.auto_advance {
+            self.set(deadline);
+            // Let other tasks see the new time before the sleeper carries on
+            tokio::task::yield_now().await;
+            return;
+        }
+
+        self.wait_until(deadline).await;
+    }
+}
@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::time::sleep;
+
+use crate::clock::clock_source::Clock;
+
+/**
+ * The host's wall clock
+ */
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    async fn sleep_until(&self, deadline: DateTime<Utc>) {
+        if let Ok(duration) = (deadline - self.now()).to_std() {
+            sleep(duration).await;
+        }
+    }
+}
@@ -0,0 +1,503 @@
+use std::{collections::HashSet, env, fmt, fs, io, path::{Path, PathBuf}};
+
+use crate::{
+    config::monitor_config::{
+        AlertWindow, ClockConfig, MonitorConfig, MonitorSettings, PollingConfig, ProviderConfig, RetentionConfig,
+        TickerConfig,
+    },
+    market_data::asset_class::AssetClass,
+};
+
+const DEFAULT_CONFIG_PATH: &str = "monitor.toml";
+const REGULAR_SESSION_MINUTES: u32 = 390;
+
+/**
+ * Everything that can go wrong while loading the config file
+ *
+ * Io: the file could not be read
+ * Parse: the file is not valid TOML, or has fields of the wrong type
+ * Invalid: the file parsed but one or more values failed validation
+ */
+#[derive(Debug)]
+pub enum ConfigError {
+    Io { path: PathBuf, source: io::Error },
+    Parse { path: PathBuf, source: toml::de::Error },
+    Invalid { path: PathBuf, reasons: Vec<String> },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => {
+                write!(f, "could not read config file {}: {}", path.display(), source)
+            }
+            ConfigError::Parse { path, source } => {
+                write!(f, "could not parse config file {}: {}", path.display(), source)
+            }
+            ConfigError::Invalid { path, reasons } => {
+                writeln!(f, "invalid config file {}:", path.display())?;
+                for reason in reasons {
+                    writeln!(f, "  - {}", reason)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/**
+ * Path of the config file, MONITOR_CONFIG if set, otherwise monitor.toml in the working directory
+ */
+pub fn config_path() -> PathBuf {
+    env::var("MONITOR_CONFIG")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_CONFIG_PATH))
+}
+
+/**
+ * Reads, parses and validates the config file at path
+ *
+ * @return the resolved settings, with one TickerConfig per ticker in file order
+ */
+pub fn load_config(path: &Path) -> Result<MonitorSettings, ConfigError> {
+    let contents = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    parse_config(&contents).map_err(|err| match err {
+        ParseFailure::Toml(source) => ConfigError::Parse { path: path.to_path_buf(), source },
+        ParseFailure::Invalid(reasons) => ConfigError::Invalid { path: path.to_path_buf(), reasons },
+    })
+}
+
+#[derive(Debug)]
+enum ParseFailure {
+    Toml(toml::de::Error),
+    Invalid(Vec<String>),
+}
+
+fn parse_config(contents: &str) -> Result<MonitorSettings, ParseFailure> {
+    let monitor_config: MonitorConfig = toml::from_str(contents).map_err(ParseFailure::Toml)?;
+
+    let tickers: Vec<TickerConfig> = monitor_config
+        .tickers
+        .iter()
+        .map(|raw| raw.resolve(&monitor_config.defaults))
+        .collect();
+
+    let mut reasons = validate_provider(&monitor_config.provider);
+    reasons.extend(validate_polling(&monitor_config.polling));
+    reasons.extend(validate_clock(&monitor_config.clock));
+    reasons.extend(validate_retention(&monitor_config.retention));
+    reasons.extend(validate(&tickers));
+    if !reasons.is_empty() {
+        return Err(ParseFailure::Invalid(reasons));
+    }
+
+    Ok(MonitorSettings {
+        provider: monitor_config.provider,
+        polling: monitor_config.polling,
+        shutdown: monitor_config.shutdown,
+        clock: monitor_config.clock,
+        retention: monitor_config.retention,
+        defaults: monitor_config.defaults,
+        tickers,
+    })
+}
+
+fn validate_provider(provider: &ProviderConfig) -> Vec<String> {
+    let mut reasons: Vec<String> = Vec::new();
+
+    if provider.fallback == Some(provider.name) {
+        reasons.push("provider.fallback must be a different provider than provider.name".to_string());
+    }
+
+    for (provider_name, rate_limit) in &provider.rate_limits {
+        if rate_limit.requests_per_minute == 0 || rate_limit.burst == Some(0) {
+            reasons.push(format!(
+                "provider.rate_limits.{:?}: requests_per_minute and burst must be at least 1",
+                provider_name
+            ));
+        }
+    }
+
+    if provider.retry.max_attempts == 0 {
+        reasons.push("provider.retry.max_attempts must be at least 1".to_string());
+    }
+
+    if provider.retry.base_delay_ms > provider.retry.max_delay_ms {
+        reasons.push("provider.retry.base_delay_ms must not be greater than provider.retry.max_delay_ms".to_string());
+    }
+
+    reasons
+}
+
+fn validate_polling(polling: &PollingConfig) -> Vec<String> {
+    let mut reasons: Vec<String> = Vec::new();
+
+    if polling.max_concurrent_fetches == 0 {
+        reasons.push("polling.max_concurrent_fetches must be at least 1".to_string());
+    }
+
+    if polling.fetch_timeout_secs == 0 {
+        reasons.push("polling.fetch_timeout_secs must be at least 1".to_string());
+    }
+
+    reasons
+}
+
+fn validate_clock(clock: &ClockConfig) -> Vec<String> {
+    let mut reasons: Vec<String> = Vec::new();
+
+    if clock.offset_hours != 0 && clock.start.is_some() {
+        reasons.push("clock.offset_hours and clock.start cannot both be set".to_string());
+    }
+
+    reasons
+}
+
+fn validate_retention(retention: &RetentionConfig) -> Vec<String> {
+    let mut reasons: Vec<String> = Vec::new();
+    let mut seen: HashSet<u32> = HashSet::new();
+
+    if retention.interval_hours == 0 {
+        reasons.push("retention.interval_hours must be at least 1".to_string());
+    }
+
+    for policy in &retention.policies {
+        let timeframe = policy.timeframe;
+
+        if timeframe == 0 || 24 * 60 % timeframe != 0 {
+            reasons.push(format!(
+                "retention.policies: timeframe must be a number of minutes that evenly divides a day, got {}",
+                timeframe
+            ));
+            continue;
+        }
+
+        if !seen.insert(timeframe) {
+            reasons.push(format!("retention.policies: timeframe {} is listed more than once", timeframe));
+        }
+
+        if policy.keep_days == 0 {
+            reasons.push(format!("retention.policies: timeframe {}: keep_days must be at least 1", timeframe));
+        }
+
+        if let Some(roll_up_to) = policy.roll_up_to
+            && (roll_up_to <= timeframe || roll_up_to % timeframe != 0 || 24 * 60 % roll_up_to != 0)
+        {
+            reasons.push(format!(
+                "retention.policies: timeframe {}: roll_up_to must be a larger multiple of the timeframe that evenly divides a day, got {}",
+                timeframe, roll_up_to
+            ));
+        }
+    }
+
+    reasons
+}
+
+/**
+ * Collects every problem with the watchlist instead of stopping at the first one,
+ * so a single startup attempt reports everything that needs fixing.
+ */
+fn validate(tickers: &[TickerConfig]) -> Vec<String> {
+    let mut reasons: Vec<String> = Vec::new();
+    let mut seen: HashSet<&str> = HashSet::new();
+
+    if tickers.is_empty() {
+        reasons.push("the watchlist is empty, add at least one [[tickers]] entry".to_string());
+    }
+
+    for ticker in tickers {
+        let symbol = ticker.symbol.as_str();
+
+        if symbol.is_empty() {
+            reasons.push("a [[tickers]] entry has an empty symbol".to_string());
+            continue;
+        }
+
+        if !seen.insert(symbol) {
+            reasons.push(format!("{}: listed more than once", symbol));
+        }
+
+        if AssetClass::infer(symbol) != ticker.asset_class {
+            reasons.push(format!(
+                "{}: the symbol's prefix does not match asset_class {:?}",
+                symbol, ticker.asset_class
+            ));
+        }
+
+        if ticker.timeframe == 0 || 24 * 60 % ticker.timeframe != 0 {
+            reasons.push(format!(
+                "{}: timeframe must be a number of minutes that evenly divides a day, got {}",
+                symbol, ticker.timeframe
+            ));
+        }
+
+        if ticker.queue_capacity < 2 {
+            reasons.push(format!(
+                "{}: queue_capacity must be at least 2 to compute a standard deviation, got {}",
+                symbol, ticker.queue_capacity
+            ));
+        }
+
+        if !(ticker.volume_spike_k > 0.0 && ticker.volume_spike_k <= 3.0) {
+            reasons.push(format!(
+                "{}: volume_spike_k must be greater than 0 and at most 3, got {}",
+                symbol, ticker.volume_spike_k
+            ));
+        }
+
+        reasons.extend(validate_alert_window(symbol, &ticker.alert_window));
+    }
+
+    reasons
+}
+
+fn validate_alert_window(symbol: &str, alert_window: &AlertWindow) -> Vec<String> {
+    let mut reasons: Vec<String> = Vec::new();
+
+    for (field, minutes) in [
+        ("after_open_minutes", alert_window.after_open_minutes),
+        ("before_close_minutes", alert_window.before_close_minutes),
+    ] {
+        if minutes > REGULAR_SESSION_MINUTES {
+            reasons.push(format!(
+                "{}: alert_window.{} must be at most the {} minute regular session, got {}",
+                symbol, field, REGULAR_SESSION_MINUTES, minutes
+            ));
+        }
+    }
+
+    reasons
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::{TimeZone, Utc};
+
+    use crate::{
+        config::monitor_config::{ProviderName, RateLimitConfig, RetentionPolicy, RetryConfig},
+        test_fixtures::ticker_config,
+    };
+
+    use super::*;
+
+    fn policy(timeframe: u32, keep_days: u32, roll_up_to: Option<u32>) -> RetentionPolicy {
+        RetentionPolicy { timeframe, keep_days, roll_up_to }
+    }
+
+    fn retention_reasons(policies: Vec<RetentionPolicy>) -> Vec<String> {
+        validate_retention(&RetentionConfig { interval_hours: 24, policies })
+    }
+
+    #[test]
+    fn accepts_roll_ups_into_coarser_timeframes() {
+        let reasons = retention_reasons(vec![policy(1, 30, Some(15)), policy(15, 730, Some(1440)), policy(60, 365, None)]);
+
+        assert!(reasons.is_empty(), "{:?}", reasons);
+    }
+
+    #[test]
+    fn rejects_roll_ups_that_do_not_cover_whole_candles() {
+        // Finer, the same size, not a multiple of the timeframe, and a multiple that does not divide a day
+        for roll_up_to in [5, 15, 20, 105] {
+            let reasons = retention_reasons(vec![policy(15, 30, Some(roll_up_to))]);
+            assert_eq!(reasons.len(), 1, "roll_up_to {}", roll_up_to);
+            assert!(reasons[0].contains("roll_up_to"));
+        }
+    }
+
+    #[test]
+    fn rejects_duplicate_policies_and_nothing_kept() {
+        let reasons = retention_reasons(vec![policy(1, 30, None), policy(1, 0, None), policy(7, 1, None)]);
+
+        assert_eq!(
+            reasons,
+            vec![
+                "retention.policies: timeframe 1 is listed more than once".to_string(),
+                "retention.policies: timeframe 1: keep_days must be at least 1".to_string(),
+                "retention.policies: timeframe must be a number of minutes that evenly divides a day, got 7".to_string(),
+            ]
+        );
+        assert_eq!(
+            validate_retention(&RetentionConfig { interval_hours: 0, policies: Vec::new() }),
+            vec!["retention.interval_hours must be at least 1".to_string()]
+        );
+    }
+
+    #[test]
+    fn retention_is_read_from_the_config_file() {
+        let settings = parse_config(
+            r#"
+            [retention]
+            interval_hours = 6
+            [[retention.policies]]
+            timeframe = 1
+            keep_days = 30
+            roll_up_to = 15
+
+            [[tickers]]
+            symbol = "QQQ"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(settings.retention.interval_hours, 6);
+        assert_eq!(settings.retention.policies[0].roll_up_to, Some(15));
+    }
+
+    #[test]
+    fn accepts_the_default_watchlist_entry() {
+        let reasons = validate(&[ticker_config("QQQ"), ticker_config("X:BTCUSD")]);
+
+        assert!(reasons.is_empty(), "{:?}", reasons);
+    }
+
+    #[test]
+    fn rejects_a_ticker_listed_twice() {
+        assert_eq!(
+            validate(&[ticker_config("QQQ"), ticker_config("SPY"), ticker_config("QQQ")]),
+            vec!["QQQ: listed more than once".to_string()]
+        );
+    }
+
+    #[test]
+    fn rejects_a_queue_too_short_for_a_standard_deviation() {
+        let ticker = TickerConfig { queue_capacity: 0, ..ticker_config("QQQ") };
+
+        assert_eq!(
+            validate(&[ticker]),
+            vec!["QQQ: queue_capacity must be at least 2 to compute a standard deviation, got 0".to_string()]
+        );
+    }
+
+    #[test]
+    fn rejects_volume_spike_k_outside_zero_to_three() {
+        for volume_spike_k in [0.0, -1.0, 3.5, f64::NAN] {
+            let reasons = validate(&[TickerConfig { volume_spike_k, ..ticker_config("QQQ") }]);
+            assert_eq!(
+                reasons,
+                vec![format!("QQQ: volume_spike_k must be greater than 0 and at most 3, got {}", volume_spike_k)]
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_alert_windows_longer_than_the_regular_session() {
+        let alert_window = AlertWindow { after_open_minutes: 390, before_close_minutes: 400 };
+
+        assert_eq!(
+            validate_alert_window("QQQ", &alert_window),
+            vec!["QQQ: alert_window.before_close_minutes must be at most the 390 minute regular session, got 400".to_string()]
+        );
+    }
+
+    #[test]
+    fn collects_every_problem_with_a_ticker() {
+        let ticker = TickerConfig {
+            timeframe: 7,
+            queue_capacity: 1,
+            alert_window: AlertWindow { after_open_minutes: 391, before_close_minutes: 15 },
+            asset_class: AssetClass::Equity,
+            ..ticker_config("X:BTCUSD")
+        };
+
+        assert_eq!(
+            validate(&[ticker]),
+            vec![
+                "X:BTCUSD: the symbol's prefix does not match asset_class Equity".to_string(),
+                "X:BTCUSD: timeframe must be a number of minutes that evenly divides a day, got 7".to_string(),
+                "X:BTCUSD: queue_capacity must be at least 2 to compute a standard deviation, got 1".to_string(),
+                "X:BTCUSD: alert_window.after_open_minutes must be at most the 390 minute regular session, got 391".to_string(),
+            ]
+        );
+        assert_eq!(validate(&[]), vec!["the watchlist is empty, add at least one [[tickers]] entry".to_string()]);
+    }
+
+    #[test]
+    fn rejects_a_provider_that_cannot_make_a_request() {
+        let provider = ProviderConfig {
+            name: ProviderName::TwelveData,
+            fallback: Some(ProviderName::TwelveData),
+            rate_limits: HashMap::from([(ProviderName::Polygon, RateLimitConfig { requests_per_minute: 5, burst: Some(0) })]),
+            retry: RetryConfig { max_attempts: 0, base_delay_ms: 1_000, max_delay_ms: 500 },
+        };
+
+        assert_eq!(
+            validate_provider(&provider),
+            vec![
+                "provider.fallback must be a different provider than provider.name".to_string(),
+                "provider.rate_limits.Polygon: requests_per_minute and burst must be at least 1".to_string(),
+                "provider.retry.max_attempts must be at least 1".to_string(),
+                "provider.retry.base_delay_ms must not be greater than provider.retry.max_delay_ms".to_string(),
+            ]
+        );
+        assert!(validate_provider(&ProviderConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn rejects_polling_that_never_fetches() {
+        let polling = PollingConfig { max_concurrent_fetches: 0, fetch_timeout_secs: 0, ..PollingConfig::default() };
+
+        assert_eq!(
+            validate_polling(&polling),
+            vec![
+                "polling.max_concurrent_fetches must be at least 1".to_string(),
+                "polling.fetch_timeout_secs must be at least 1".to_string(),
+            ]
+        );
+        assert!(validate_polling(&PollingConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn rejects_an_offset_clock_with_a_start() {
+        let start = Some(Utc.with_ymd_and_hms(2025, 7, 14, 13, 0, 0).unwrap());
+
+        assert_eq!(
+            validate_clock(&ClockConfig { offset_hours: -48, start }),
+            vec!["clock.offset_hours and clock.start cannot both be set".to_string()]
+        );
+        assert!(validate_clock(&ClockConfig { offset_hours: -48, start: None }).is_empty());
+        assert!(validate_clock(&ClockConfig { offset_hours: 0, start }).is_empty());
+    }
+
+    #[test]
+    fn reports_every_section_of_the_file_at_once() {
+        let err = parse_config(
+            r#"
+            [polling]
+            max_concurrent_fetches = 0
+
+            [clock]
+            offset_hours = -48
+            start = "2025-07-14T13:00:00Z"
+
+            [[tickers]]
+            symbol = "QQQ"
+            [[tickers]]
+            symbol = "QQQ"
+            volume_spike_k = 4.0
+            "#,
+        )
+        .unwrap_err();
+
+        match err {
+            ParseFailure::Invalid(reasons) => assert_eq!(
+                reasons,
+                vec![
+                    "polling.max_concurrent_fetches must be at least 1".to_string(),
+                    "clock.offset_hours and clock.start cannot both be set".to_string(),
+                    "QQQ: listed more than once".to_string(),
+                    "QQQ: volume_spike_k must be greater than 0 and at most 3, got 4".to_string(),
+                ]
+            ),
+            ParseFailure::Toml(e) => panic!("expected validation to fail, got {}", e),
+        }
+    }
+}
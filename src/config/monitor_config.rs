@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::{
+    market_calendar::{calendar::TradingSession, session::Session},
+    market_data::asset_class::AssetClass,
+};
+
+/**
+ * Typed representation of the monitor config file.
+ *
+ * @provider: which market data vendor to poll
+ * @polling: how the polling loop fetches
+ * @shutdown: how long a graceful shutdown may take
+ * @clock: where the polling loop reads the time from
+ * @retention: how long saved candles are kept
+ * @defaults: values used for any ticker which does not override them
+ * @tickers: the watchlist, one entry per [[tickers]] table
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MonitorConfig {
+    #[serde(default)]
+    pub provider: ProviderConfig,
+    #[serde(default)]
+    pub polling: PollingConfig,
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+    #[serde(default)]
+    pub clock: ClockConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    #[serde(default)]
+    pub defaults: TickerDefaults,
+    #[serde(default)]
+    pub tickers: Vec<RawTickerConfig>,
+}
+
+/**
+ * The config file after defaults have been applied and every value validated
+ */
+#[derive(Debug, Clone)]
+pub struct MonitorSettings {
+    pub provider: ProviderConfig,
+    pub polling: PollingConfig,
+    pub shutdown: ShutdownConfig,
+    pub clock: ClockConfig,
+    pub retention: RetentionConfig,
+    pub defaults: TickerDefaults,
+    pub tickers: Vec<TickerConfig>,
+}
+
+/**
+ * Market data vendors the monitor knows how to poll
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderName {
+    Polygon,
+    #[serde(rename = "twelvedata")]
+    TwelveData,
+}
+
+/**
+ * @name: the provider every fetch goes to first
+ * @fallback: an optional second provider, used when the first one fails
+ * @rate_limits: per provider overrides of the built in plan limits, keyed by provider name
+ * @retry: how transient failures are retried, shared by every provider
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProviderConfig {
+    pub name: ProviderName,
+    pub fallback: Option<ProviderName>,
+    #[serde(default)]
+    pub rate_limits: HashMap<ProviderName, RateLimitConfig>,
+    #[serde(default)]
+    pub retry: RetryConfig,
+}
+
+impl Default for ProviderConfig {
+    fn default() -> Self {
+        ProviderConfig {
+            name: ProviderName::Polygon,
+            fallback: None,
+            rate_limits: HashMap::new(),
+            retry: RetryConfig::default(),
+        }
+    }
+}
+
+/**
+ * Token bucket settings for one provider, burst defaults to requests_per_minute
+ */
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub requests_per_minute: u32,
+    pub burst: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_attempts: 3,
+            base_delay_ms: 500,
+            max_delay_ms: 30_000,
+        }
+    }
+}
+
+/**
+ * @max_concurrent_fetches: how many tickers may be fetched at the same time
+ * @fetch_timeout_secs: how long a single ticker's fetch, including retries, may take before it is skipped
+ * @settle_delay_secs: how long after a candle closes to wait before fetching it
+ */
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct PollingConfig {
+    pub max_concurrent_fetches: usize,
+    pub fetch_timeout_secs: u64,
+    pub settle_delay_secs: u64,
+}
+
+impl Default for PollingConfig {
+    fn default() -> Self {
+        PollingConfig {
+            max_concurrent_fetches: 4,
+            fetch_timeout_secs: 120,
+            settle_delay_secs: 5,
+        }
+    }
+}
+
+/**
+ * @deadline_secs: time allowed between the shutdown signal and the process exiting,
+ * covering in flight fetches, flushing to the database and draining HTTP connections
+ */
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct ShutdownConfig {
+    pub deadline_secs: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        ShutdownConfig { deadline_secs: 30 }
+    }
+}
+
+/**
+ * Leave both unset to use the system clock
+ *
+ * @offset_hours: shift the clock by this many hours, e.g. -48 to poll a data plan delayed by two days
+ * @start: run the clock from this instant instead, e.g. "2025-07-14T13:00:00Z" to replay that session live
+ */
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct ClockConfig {
+    pub offset_hours: i64,
+    pub start: Option<DateTime<Utc>>,
+}
+
+/**
+ * Candles of a timeframe without a policy are kept forever
+ *
+ * @interval_hours: how often the retention job runs, it also runs once on startup
+ * @policies: one entry per [[retention.policies]] table
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct RetentionConfig {
+    pub interval_hours: u64,
+    pub policies: Vec<RetentionPolicy>,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        RetentionConfig {
+            interval_hours: 24,
+            policies: Vec::new(),
+        }
+    }
+}
+
+/**
+ * @timeframe: candle size in minutes the policy applies to
+ * @keep_days: candles older than this many days are deleted
+ * @roll_up_to: if set, candles are first combined into candles of this timeframe and saved
+ */
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetentionPolicy {
+    pub timeframe: u32,
+    pub keep_days: u32,
+    pub roll_up_to: Option<u32>,
+}
+
+/**
+ * Settings shared by every ticker in the watchlist unless overridden per ticker
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct TickerDefaults {
+    pub timeframe: u32,
+    pub queue_capacity: u32,
+    pub volume_spike_k: f64,
+    pub alert_window: AlertWindow,
+    pub extended_hours: bool,
+}
+
+impl Default for TickerDefaults {
+    fn default() -> Self {
+        TickerDefaults {
+            timeframe: 15,
+            queue_capacity: 50,
+            volume_spike_k: 3.0,
+            alert_window: AlertWindow::default(),
+            extended_hours: false,
+        }
+    }
+}
+
+/**
+ * A [[tickers]] entry as written in the file, any missing value falls back to [defaults]
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawTickerConfig {
+    pub symbol: String,
+    pub timeframe: Option<u32>,
+    pub queue_capacity: Option<u32>,
+    pub volume_spike_k: Option<f64>,
+    pub alert_window: Option<AlertWindow>,
+    pub extended_hours: Option<bool>,
+    pub asset_class: Option<AssetClass>,
+}
+
+/**
+ * Fully resolved settings for one ticker in the watchlist
+ *
+ * @symbol: the Polygon ticker symbol, e.g. QQQ, X:BTCUSD or C:EURUSD
+ * @timeframe: candle size in minutes
+ * @queue_capacity: how many candles are kept in memory for the ticker
+ * @volume_spike_k: how many standard deviations above the mean counts as a volume spike
+ * @alert_window: the regular session candles during which a volume spike alert may fire
+ * @extended_hours: also poll the pre-market and after-hours sessions
+ * @asset_class: which trading calendar the ticker follows
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickerConfig {
+    pub symbol: String,
+    pub timeframe: u32,
+    pub queue_capacity: u32,
+    pub volume_spike_k: f64,
+    pub alert_window: AlertWindow,
+    pub extended_hours: bool,
+    pub asset_class: AssetClass,
+}
+
+impl TickerConfig {
+    /**
+     * Regular hours, plus pre-market and after-hours when extended_hours is on
+     */
+    pub fn polled_sessions(&self) -> Vec<Session> {
+        Session::ALL.into_iter().filter(|session| !session.is_extended() || self.extended_hours).collect()
+    }
+}
+
+/**
+ * Configs for which time candles to consider for the standard deviation alert
+ * Measured from the session's open and close, so half days and daylight saving move the window with the session.
+ *
+ * @after_open_minutes: candles closing this many minutes or less after the open may fire
+ * @before_close_minutes: candles closing less than this many minutes before the close may fire
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertWindow {
+    pub after_open_minutes: u32,
+    pub before_close_minutes: u32,
+}
+
+impl Default for AlertWindow {
+    fn default() -> Self {
+        AlertWindow {
+            after_open_minutes: 45,
+            before_close_minutes: 15,
+        }
+    }
+}
+
+impl AlertWindow {
+    /**
+     * @session: the trading session the candle belongs to
+     * @candle_close: when the candle being analyzed closed
+     */
+    pub fn is_time_to_fire(&self, session: &TradingSession, candle_close: DateTime<Utc>) -> bool {
+        session.contains_candle_close(candle_close)
+            && (session.minutes_since_open(candle_close) <= self.after_open_minutes as i64
+                || session.minutes_until_close(candle_close) < self.before_close_minutes as i64)
+    }
+}
+
+impl RawTickerConfig {
+    pub fn resolve(&self, defaults: &TickerDefaults) -> TickerConfig {
+        // Left out, the asset class comes from the symbol's prefix, e.g. X:BTCUSD is crypto
+        let symbol = self.symbol.trim().to_uppercase();
+        let asset_class = self.asset_class.unwrap_or_else(|| AssetClass::infer(&symbol));
+
+        TickerConfig {
+            symbol: asset_class.polygon_symbol(&symbol),
+            timeframe: self.timeframe.unwrap_or(defaults.timeframe),
+            queue_capacity: self.queue_capacity.unwrap_or(defaults.queue_capacity),
+            volume_spike_k: self.volume_spike_k.unwrap_or(defaults.volume_spike_k),
+            alert_window: self.alert_window.unwrap_or(defaults.alert_window),
+            extended_hours: self.extended_hours.unwrap_or(defaults.extended_hours),
+            asset_class,
+        }
+    }
+}
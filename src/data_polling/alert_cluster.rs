@@ -1,25 +1,45 @@
+use serde::Serialize;
+
+use crate::indicators::indicator_set::IndicatorValues;
+
+// RSI levels an alert fires at when the analyzed candle crosses them
+pub const RSI_OVERBOUGHT: f64 = 70.0;
+pub const RSI_OVERSOLD: f64 = 30.0;
+
 /*
 * A collection of alerts which are used transmit messages across functions
 * Informs if an email alert should be fired or not,
 * And what the contents of the email should be
 *
 * @is_volume_spike: shows if theres a volume spike or not
+* @is_rsi_overbought: the RSI crossed above RSI_OVERBOUGHT on the analyzed candle
+* @is_rsi_oversold: the RSI crossed below RSI_OVERSOLD on the analyzed candle
+* @indicators: the analyzed candle's indicators, for rules to check and alerts to report
 *
 */
 pub struct AlertCluster{
 
- pub is_volume_spike: bool  
+ pub is_volume_spike: bool,
+ pub is_rsi_overbought: bool,
+ pub is_rsi_oversold: bool,
+ pub indicators: IndicatorValues,
   
 }
 
 pub struct AlertClusterBuilder {
-  is_volume_spike: bool  
+  is_volume_spike: bool,
+  is_rsi_overbought: bool,
+  is_rsi_oversold: bool,
+  indicators: IndicatorValues,
 }
 
 impl AlertClusterBuilder {
     pub fn new() -> Self {
         AlertClusterBuilder {
             is_volume_spike: false, 
+            is_rsi_overbought: false,
+            is_rsi_oversold: false,
+            indicators: IndicatorValues::default(),
         }
     }
 
@@ -28,6 +48,25 @@ impl AlertClusterBuilder {
         self
     }
 
+    pub fn set_indicators(mut self, indicators: IndicatorValues) -> Self {
+        self.indicators = indicators;
+        self
+    }
+
+    /**
+     * Fires when the indicators set with set_indicators cross into overbought or oversold.
+     * Only the crossing fires, so a ticker staying overbought does not alert on every candle.
+     *
+     * @param: previous: the candle before's indicators
+     */
+    pub fn check_rsi_crossing(mut self, previous: &IndicatorValues) -> Self {
+        if let (Some(previous_rsi), Some(rsi)) = (previous.rsi_14, self.indicators.rsi_14) {
+            self.is_rsi_overbought = previous_rsi <= RSI_OVERBOUGHT && rsi > RSI_OVERBOUGHT;
+            self.is_rsi_oversold = previous_rsi >= RSI_OVERSOLD && rsi < RSI_OVERSOLD;
+        }
+        self
+    }
+
     //Creates a constraint on the volume spike
     // A condition that must be satisfied or it will not fire
     pub fn filter_volume_spike(mut self, is_alert_fireable: bool) -> Self{ 
@@ -38,6 +77,9 @@ impl AlertClusterBuilder {
     pub fn build(self) -> AlertCluster {
         AlertCluster {
             is_volume_spike: self.is_volume_spike,
+            is_rsi_overbought: self.is_rsi_overbought,
+            is_rsi_oversold: self.is_rsi_oversold,
+            indicators: self.indicators,
         }
     }
 }
@@ -45,7 +87,64 @@ impl AlertClusterBuilder {
 
 impl AlertCluster {
   pub fn is_alert_fireable(&self) -> bool {
-    return self.is_volume_spike;
+    return self.is_volume_spike || self.is_rsi_overbought || self.is_rsi_oversold;
+  }
+
+  /**
+   * Names of every alert in the cluster that fired, as stored in the alerts table
+   */
+  pub fn fired_alert_types(&self) -> Vec<&'static str> {
+    let mut fired = Vec::new();
+    if self.is_volume_spike {
+      fired.push("volume_spike");
+    }
+    if self.is_rsi_overbought {
+      fired.push("rsi_overbought");
+    }
+    if self.is_rsi_oversold {
+      fired.push("rsi_oversold");
+    }
+    return fired;
   }
 
-}
\ No newline at end of file
+}
+
+/**
+ * An alert that fired, kept until it has been written to the database
+ *
+ * @candle_timestamp: start of the candle that triggered the alert, in milliseconds
+ * @fired_at: when the alert fired, in milliseconds
+ * @indicators: the triggering candle's indicators when the alert fired
+ */
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertRecord {
+  pub ticker: String,
+  pub alert_type: String,
+  pub candle_timestamp: u64,
+  pub fired_at: i64,
+  pub indicators: IndicatorValues,
+}
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn with_rsi(rsi_14: f64) -> IndicatorValues {
+    IndicatorValues { rsi_14: Some(rsi_14), ..IndicatorValues::default() }
+  }
+
+  #[test]
+  fn rsi_alerts_fire_only_on_the_crossing() {
+    let crossing_up = AlertClusterBuilder::new().set_indicators(with_rsi(71.0)).check_rsi_crossing(&with_rsi(69.0)).build();
+    assert_eq!(crossing_up.fired_alert_types(), vec!["rsi_overbought"]);
+
+    let staying_up = AlertClusterBuilder::new().set_indicators(with_rsi(75.0)).check_rsi_crossing(&with_rsi(71.0)).build();
+    assert!(!staying_up.is_alert_fireable());
+
+    let crossing_down = AlertClusterBuilder::new().set_indicators(with_rsi(29.0)).check_rsi_crossing(&with_rsi(30.0)).build();
+    assert_eq!(crossing_down.fired_alert_types(), vec!["rsi_oversold"]);
+
+    // Nothing to compare against while the RSI warms up
+    let warming_up = AlertClusterBuilder::new().set_indicators(with_rsi(80.0)).check_rsi_crossing(&IndicatorValues::default()).build();
+    assert!(!warming_up.is_alert_fireable());
+  }
+}
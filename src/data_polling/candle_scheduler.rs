@@ -0,0 +1,167 @@
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::data_polling::constants::MINUTES_TO_MILIS;
+
+/**
+ * Works out when the polling loop should next wake up
+ *
+ * Candles close on multiples of their timeframe since midnight UTC, e.g. a 15 minute candle closes at
+ * :00, :15, :30 and :45. The loop wakes settle_delay after the earliest upcoming close,
+ * giving the provider time to publish the finished candle.
+ */
+pub struct CandleScheduler {
+    settle_delay: Duration,
+}
+
+/**
+ * The next wake up and which tickers have a candle closing at it
+ *
+ * @wake_at: when to wake, the candle close plus the settle delay
+ * @candle_close: the candle boundary being waited for
+ * @due_tickers: every ticker whose timeframe has a candle closing at candle_close
+ */
+#[derive(Debug, PartialEq)]
+pub struct ScheduledWake {
+    pub wake_at: DateTime<Utc>,
+    pub candle_close: DateTime<Utc>,
+    pub due_tickers: Vec<String>,
+}
+
+impl CandleScheduler {
+    pub fn new(settle_delay: Duration) -> Self {
+        CandleScheduler { settle_delay }
+    }
+
+    /**
+     * @tickers: (symbol, timeframe in minutes) for every ticker being polled
+     * @now: the current time, wakes strictly after it are returned
+     *
+     * @return None when there are no tickers to schedule
+     */
+    pub fn next_wake(&self, tickers: &[(String, u32)], now: DateTime<Utc>) -> Option<ScheduledWake> {
+        let settle_delay = TimeDelta::from_std(self.settle_delay).unwrap_or(TimeDelta::zero());
+        // A candle that closed less than settle_delay ago has not been fetched yet, so look back that far
+        let after = now - settle_delay;
+
+        let candle_close = tickers
+            .iter()
+            .map(|(_, timeframe)| next_candle_close(after, *timeframe))
+            .min()?;
+
+        let due_tickers = tickers
+            .iter()
+            .filter(|(_, timeframe)| is_candle_close(candle_close, *timeframe))
+            .map(|(symbol, _)| symbol.clone())
+            .collect();
+
+        Some(ScheduledWake {
+            wake_at: candle_close + settle_delay,
+            candle_close,
+            due_tickers,
+        })
+    }
+}
+
+impl ScheduledWake {
+    /**
+     * How long to sleep from now until wake_at, zero if it has already passed
+     */
+    pub fn sleep_duration(&self, now: DateTime<Utc>) -> Duration {
+        (self.wake_at - now).to_std().unwrap_or(Duration::ZERO)
+    }
+}
+
+/**
+ * The first candle close strictly after the given time
+ */
+fn next_candle_close(after: DateTime<Utc>, timeframe: u32) -> DateTime<Utc> {
+    let timeframe_millis = timeframe as i64 * MINUTES_TO_MILIS;
+    let millis = after.timestamp_millis();
+    let next = (millis.div_euclid(timeframe_millis) + 1) * timeframe_millis;
+
+    DateTime::from_timestamp_millis(next).expect("candle close is within chrono's range")
+}
+
+fn is_candle_close(at: DateTime<Utc>, timeframe: u32) -> bool {
+    at.timestamp_millis() % (timeframe as i64 * MINUTES_TO_MILIS) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use crate::market_calendar::{calendar::MarketCalendar, session::Session};
+
+    use super::*;
+
+    fn utc(month: u32, day: u32, hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, month, day, hour, minute, second).unwrap()
+    }
+
+    fn tickers(timeframes: &[(&str, u32)]) -> Vec<(String, u32)> {
+        timeframes.iter().map(|(symbol, timeframe)| (symbol.to_string(), *timeframe)).collect()
+    }
+
+    #[test]
+    fn wakes_after_the_earliest_close_with_every_ticker_due_then() {
+        let scheduler = CandleScheduler::new(Duration::from_secs(5));
+        let tickers = tickers(&[("QQQ", 15), ("NVDA", 60), ("X:BTCUSD", 5)]);
+
+        let wake = scheduler.next_wake(&tickers, utc(7, 14, 13, 56, 0)).unwrap();
+        assert_eq!(wake.candle_close, utc(7, 14, 14, 0, 0));
+        assert_eq!(wake.wake_at, utc(7, 14, 14, 0, 5));
+        assert_eq!(wake.due_tickers, vec!["QQQ", "NVDA", "X:BTCUSD"]);
+
+        let wake = scheduler.next_wake(&tickers, wake.wake_at).unwrap();
+        assert_eq!(wake.candle_close, utc(7, 14, 14, 5, 0));
+        assert_eq!(wake.due_tickers, vec!["X:BTCUSD"]);
+
+        assert_eq!(scheduler.next_wake(&[], utc(7, 14, 14, 0, 0)), None);
+    }
+
+    #[test]
+    fn a_close_inside_the_settle_delay_is_still_waited_for() {
+        let scheduler = CandleScheduler::new(Duration::from_secs(5));
+        let tickers = tickers(&[("QQQ", 15)]);
+
+        let wake = scheduler.next_wake(&tickers, utc(7, 14, 14, 0, 3)).unwrap();
+        assert_eq!(wake.candle_close, utc(7, 14, 14, 0, 0));
+        assert_eq!(wake.wake_at, utc(7, 14, 14, 0, 5));
+
+        // Woken exactly at wake_at, the same close is not scheduled twice
+        let wake = scheduler.next_wake(&tickers, wake.wake_at).unwrap();
+        assert_eq!(wake.candle_close, utc(7, 14, 14, 15, 0));
+    }
+
+    #[test]
+    fn daily_candles_close_at_midnight_utc_across_the_month_end() {
+        let scheduler = CandleScheduler::new(Duration::ZERO);
+
+        let wake = scheduler.next_wake(&tickers(&[("X:BTCUSD", 1440)]), utc(7, 31, 23, 59, 59)).unwrap();
+
+        assert_eq!(wake.candle_close, utc(8, 1, 0, 0, 0));
+    }
+
+    #[test]
+    fn the_first_closes_after_a_weekend_follow_the_clock_change() {
+        let scheduler = CandleScheduler::new(Duration::from_secs(5));
+        let calendar = MarketCalendar::nyse();
+        let tickers = tickers(&[("QQQ", 15)]);
+
+        // Walks wake by wake from just after Friday's closing bell, 16:00 EDT, to the first close in the session
+        let first_close_in = |session: Session| {
+            let mut wake = scheduler.next_wake(&tickers, utc(10, 31, 20, 0, 5)).unwrap();
+            while calendar.candle_session(wake.candle_close) != Some(session) {
+                wake = scheduler.next_wake(&tickers, wake.wake_at).unwrap();
+            }
+            wake.candle_close
+        };
+
+        assert_eq!(first_close_in(Session::AfterHours), utc(10, 31, 20, 15, 0));
+        // Daylight saving ended on Sunday, so Monday's 4:00 and 9:30 EST are an hour later in UTC than Friday's
+        assert_eq!(first_close_in(Session::PreMarket), utc(11, 3, 9, 15, 0));
+        assert_eq!(first_close_in(Session::Regular), utc(11, 3, 14, 45, 0));
+    }
+}
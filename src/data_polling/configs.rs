@@ -1,9 +0,0 @@
-
-/**
- * Configs for which time candles to ignore for calculating standard deviation alert
-*/
-pub const MIN_HOUR: u32 = 7;
-pub const MIN_MINUTE: u32 = 15;
-
-pub const MAX_HOUR: u32 = 12;
-pub const MAX_MIN: u32 =  45;
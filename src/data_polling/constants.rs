@@ -1,3 +1,2 @@
 pub const MINUTES_TO_MILIS: i64 = 60 * 1000;
-pub const QUEUE_CAPACITY: u32 = 50;
-pub const IS_DB_ENABLED: bool = true;
\ No newline at end of file
+pub const IS_DB_ENABLED: bool = true;
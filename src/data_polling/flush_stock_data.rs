@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use crate::{
+  data_polling::alert_cluster::AlertRecord,
+  database::{
+    alert_repository::AlertRepository,
+    candle_repository::CandleRepository,
+    database_error::DatabaseError,
+    models::{NewAlert, NewStock},
+    repositories::Repositories,
+  },
+  polygon_api::stock::StockData,
+};
+
+/**
+ * Writes every candle of a ticker that is not in the database yet, and moves its watermark forward
+ *
+ * @return how many candles were written
+ */
+pub async fn flush_stock_data(stock_data: &mut StockData<'_>, candles: &dyn CandleRepository) -> Result<usize, DatabaseError> {
+  let new_stocks = unsaved_stock_rows(stock_data);
+
+  let newest = match stock_data.last_timestamp() {
+    Some(newest) if !new_stocks.is_empty() => newest,
+    _ => return Ok(0),
+  };
+
+  let written = candles.save_candles(new_stocks).await?;
+
+  stock_data.persisted_until = Some(newest);
+  return Ok(written);
+}
+
+/**
+ * Rows for the stocks table, one per candle held in memory that is newer than the watermark
+ */
+pub fn unsaved_stock_rows(stock_data: &StockData<'_>) -> Vec<NewStock> {
+  return stock_data.unsaved_stock_data().map(|datum| {
+    let indicators = &datum.indicators;
+    NewStock {
+      ticker: stock_data.name.clone(),
+      open: datum.open,
+      close: datum.close,
+      high: datum.high,
+      low: datum.low,
+      ema_9: indicators.ema_9,
+      timestamp: datum.timestamp as i64,
+      timeframe: stock_data.config.timeframe as i32,
+      volume: datum.volume as i64,
+      ema_20: indicators.ema_20,
+      vwap: datum.vwap,
+      trade_count: datum.trade_count.map(|trade_count| trade_count as i32),
+      session: datum.session.as_str().to_string(),
+      rsi_14: indicators.rsi_14,
+      macd: indicators.macd.map(|macd| macd.macd),
+      macd_signal: indicators.macd.map(|macd| macd.signal),
+      macd_histogram: indicators.macd.map(|macd| macd.histogram),
+      bollinger_middle: indicators.bollinger.map(|bollinger| bollinger.middle),
+      bollinger_upper: indicators.bollinger.map(|bollinger| bollinger.upper),
+      bollinger_lower: indicators.bollinger.map(|bollinger| bollinger.lower),
+      rsi_average_gain: datum.indicator_state.rsi_average_gain,
+      rsi_average_loss: datum.indicator_state.rsi_average_loss,
+      macd_fast_ema: datum.indicator_state.macd_fast_ema,
+      macd_slow_ema: datum.indicator_state.macd_slow_ema,
+    }
+  }).collect();
+}
+
+/**
+ * Writes alerts that fired but are not in the database yet.
+ * Alerts are only removed from pending once they have been written.
+ */
+pub async fn flush_alerts(pending_alerts: &mut Vec<AlertRecord>, alerts: &dyn AlertRepository) -> Result<usize, DatabaseError> {
+  if pending_alerts.is_empty() {
+    return Ok(0);
+  }
+
+  let new_alerts: Vec<NewAlert> = pending_alerts.iter().map(|alert| {
+    let indicators = &alert.indicators;
+    NewAlert {
+      ticker: alert.ticker.clone(),
+      alert_type: alert.alert_type.clone(),
+      candle_timestamp: alert.candle_timestamp as i64,
+      fired_at: alert.fired_at,
+      ema_9: indicators.ema_9,
+      ema_20: indicators.ema_20,
+      rsi_14: indicators.rsi_14,
+      macd: indicators.macd.map(|macd| macd.macd),
+      macd_signal: indicators.macd.map(|macd| macd.signal),
+      macd_histogram: indicators.macd.map(|macd| macd.histogram),
+      bollinger_middle: indicators.bollinger.map(|bollinger| bollinger.middle),
+      bollinger_upper: indicators.bollinger.map(|bollinger| bollinger.upper),
+      bollinger_lower: indicators.bollinger.map(|bollinger| bollinger.lower),
+    }
+  }).collect();
+
+  let written = alerts.save_alerts(new_alerts).await?;
+
+  pending_alerts.clear();
+  return Ok(written);
+}
+
+/**
+ * Last step of a graceful shutdown: persists whatever is only held in memory.
+ * Failures are logged per ticker so one bad write does not stop the rest from being saved.
+ * Without a database there is nothing to do.
+ */
+pub async fn flush_on_shutdown(
+  stock_data_map: &mut HashMap<String, StockData<'_>>,
+  pending_alerts: &mut Vec<AlertRecord>,
+  database: Option<&Repositories>,
+) {
+  let repositories = match database {
+    Some(repositories) => repositories,
+    None => return,
+  };
+
+  for (ticker, stock_data) in stock_data_map.iter_mut() {
+    match flush_stock_data(stock_data, repositories.candles.as_ref()).await {
+      Ok(written) => println!("Flushed {} candles for {}", written, ticker),
+      Err(e) => eprintln!("Failed to flush candles for {}: {}", ticker, e),
+    }
+  }
+
+  match flush_alerts(pending_alerts, repositories.alerts.as_ref()).await {
+    Ok(written) => println!("Flushed {} alerts", written),
+    Err(e) => eprintln!("Failed to flush {} alerts: {}", pending_alerts.len(), e),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    database::in_memory_repository::InMemoryRepository,
+    indicators::{bollinger::BollingerValue, indicator_set::IndicatorValues, macd::MacdValue},
+    market_calendar::session::Session,
+    test_fixtures::{bar, ticker_config, wavy_closes},
+  };
+
+  use super::*;
+
+  #[test]
+  fn unsaved_rows_carry_every_indicator_column() {
+    let mut stock_data = StockData::new(ticker_config("QQQ"));
+    for (timestamp, close) in wavy_closes(40).into_iter().enumerate() {
+      stock_data.add_stock_data(&bar(timestamp as u64, close), Session::Regular);
+    }
+
+    let rows = unsaved_stock_rows(&stock_data);
+    assert_eq!(rows.len(), 40);
+    assert_eq!(rows[0].macd, None);
+    assert_eq!(rows[0].rsi_average_gain, None);
+
+    let newest = stock_data.stock_data.back().unwrap();
+    let (macd, bollinger, state) = (newest.indicators.macd.unwrap(), newest.indicators.bollinger.unwrap(), newest.indicator_state);
+    let row = &rows[39];
+    assert_eq!((row.ema_9, row.ema_20, row.rsi_14), (newest.indicators.ema_9, newest.indicators.ema_20, newest.indicators.rsi_14));
+    assert_eq!((row.macd, row.macd_signal, row.macd_histogram), (Some(macd.macd), Some(macd.signal), Some(macd.histogram)));
+    assert_eq!(
+      (row.bollinger_middle, row.bollinger_upper, row.bollinger_lower),
+      (Some(bollinger.middle), Some(bollinger.upper), Some(bollinger.lower))
+    );
+    assert_eq!((row.rsi_average_gain, row.rsi_average_loss), (state.rsi_average_gain, state.rsi_average_loss));
+    assert_eq!((row.macd_fast_ema, row.macd_slow_ema), (state.macd_fast_ema, state.macd_slow_ema));
+    assert!(state.rsi_average_gain.is_some() && state.macd_slow_ema.is_some());
+  }
+
+  #[tokio::test]
+  async fn alerts_are_saved_with_their_indicators() {
+    let repository = InMemoryRepository::new();
+    let indicators = IndicatorValues {
+      ema_9: Some(101.0),
+      ema_20: None,
+      rsi_14: Some(72.5),
+      macd: Some(MacdValue { macd: 0.5, signal: 0.3, histogram: 0.2 }),
+      bollinger: Some(BollingerValue { middle: 100.0, upper: 104.0, lower: 96.0 }),
+    };
+    let mut pending_alerts = vec![AlertRecord {
+      ticker: "QQQ".to_string(),
+      alert_type: "rsi_overbought".to_string(),
+      candle_timestamp: 60_000,
+      fired_at: 120_000,
+      indicators,
+    }];
+
+    assert_eq!(flush_alerts(&mut pending_alerts, &repository).await.unwrap(), 1);
+    assert!(pending_alerts.is_empty());
+
+    let saved = &repository.recent_alerts(None, 10).await.unwrap()[0];
+    assert_eq!((saved.ema_9, saved.ema_20, saved.rsi_14), (Some(101.0), None, Some(72.5)));
+    assert_eq!((saved.macd, saved.macd_signal, saved.macd_histogram), (Some(0.5), Some(0.3), Some(0.2)));
+    assert_eq!((saved.bollinger_middle, saved.bollinger_upper, saved.bollinger_lower), (Some(100.0), Some(104.0), Some(96.0)));
+  }
+}
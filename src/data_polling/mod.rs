@@ -1,6 +1,7 @@
+pub mod candle_scheduler;
 pub mod polling_loop;
 pub mod alert_cluster;
 pub mod setup_stock_data;
-pub mod manage_offset;
-pub mod configs;
-pub mod constants;
\ No newline at end of file
+pub mod constants;
+pub mod flush_stock_data;
+pub mod watchlist_diff;
@@ -1,93 +1,437 @@
-use std::{collections::HashMap, thread, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
-use dotenv::dotenv;
-use chrono::{Local, Timelike};
-use std::env;
+use tokio::{sync::{watch, Semaphore}, task::JoinSet, time::timeout};
 
 use crate::{
-  aws_ses::send_email::send_email, 
-  data_polling::{constants::MINUTES_TO_MILIS, manage_offset::{manage_offset, parse_offsetted_time}},
-  polygon_api::{fetch_data::fetch_data, stock::StockData},
+  clock::clock_source::Clock,
+  database::repositories::Repositories,
+  config::monitor_config::{PollingConfig, TickerConfig},
+  data_polling::{
+    candle_scheduler::CandleScheduler,
+    alert_cluster::AlertRecord,
+    flush_stock_data::{flush_alerts, flush_stock_data},
+    setup_stock_data::setup_ticker_stock_data,
+    watchlist_diff::WatchlistDiff,
+  },
+  market_data::{asset_class::display_name, fetch_error::FetchError, market_data_provider::MarketDataProvider},
+  notifier::alert_notifier::Notifier,
+  polygon_api::{stock::StockData, stock_data_response::PriceDatum},
   
   };
 
 
+/**
+ * Everything the polling loop works with besides the tickers, live or replaying
+ *
+ * @field provider: where candles are fetched from
+ * @field polling_config: concurrency, timeouts and the settle delay after each candle close
+ * @field clock: the time candles are scheduled and alerts are judged by, shifted or simulated when testing
+ * @field notifier: where fired alerts are sent, email when live or a captured report when replaying
+ * @field database: where each poll's new candles and alerts are saved, None when replaying
+ */
+pub struct PollingContext {
+  pub provider: Arc<dyn MarketDataProvider>,
+  pub polling_config: PollingConfig,
+  pub clock: Arc<dyn Clock>,
+  pub notifier: Arc<dyn Notifier>,
+  pub database: Option<Repositories>,
+}
+
 /**
  * Main polling procedure, should perform the steps in this order
  * 
  * 1. Fetch the data related to a current stock
  * 2. Perform analysis on it: get the standard deviation
  * 3. Determine whether the state of data warrants sending an alert. If so send the alert
+ *
+ * Each pass sleeps until the next candle close plus the settle delay, then fetches the tickers whose candle closed.
+ * A watchlist reload wakes the loop early, so tickers can be added or removed while running.
+ * Data is fetched through whichever MarketDataProvider was selected in the config,
+ * with every due ticker fetched concurrently.
+ *
+ * Returns once shutdown_rx flips to true, after any fetches already in flight have been applied.
+ *
+ * @return alerts that fired but have not been written to the database yet, every alert when replaying
  */
-pub async fn monitor_stock_data(stock_data_map: &mut HashMap<String, StockData<'_>>) {
-
-  // TODO put in real values here
-  let timeframe: u32  = 15;  
-  dotenv().ok();
+pub async fn monitor_stock_data(
+  stock_data_map: &mut HashMap<String, StockData<'_>>,
+  mut watchlist_rx: watch::Receiver<Vec<TickerConfig>>,
+  mut shutdown_rx: watch::Receiver<bool>,
+  context: PollingContext,
+) -> Vec<AlertRecord> {
+  let PollingContext { provider, polling_config, clock, notifier, database } = context;
+  let mut pending_alerts: Vec<AlertRecord> = Vec::new();
 
-  let api_key =  env::var("POLYGON_API_KEY") 
-        .expect("Expecting POLYGON_API_KEY to be set"); // TODO  move API key out of here, make global.
-  let is_using_timestamp = false;
-  let mut prev_fetched_min: u32  = 61; //First value is 61 because we will never be at 61 minutes  in a traditional clock
-  let keys: Vec<String> = stock_data_map.keys().cloned().collect(); 
-  
-  parse_offsetted_time(manage_offset());
+  let scheduler = CandleScheduler::new(Duration::from_secs(polling_config.settle_delay_secs));
+  println!("Polling from {}", clock.now());
 
   loop {
-    let now = Local::now();
-    
-    let timestamp_to: i64 = if is_using_timestamp  {now.timestamp_millis() } else {manage_offset()};
-    let timestamp_from: i64 = timestamp_to -  timeframe as i64 * MINUTES_TO_MILIS;
-
-    let now_hour = now.hour();
-    let now_min =  now.minute();
-    
-    let is_market_closed =  is_market_closed(&now);  
-    let is_already_fetched = now_min - now_min % timeframe == prev_fetched_min;
- 
-    if is_market_closed || is_already_fetched {
-      println!("{:?}", if is_market_closed { "Waiting for market to open"} else { "Waiting for data to become availible before fetching"});
-      thread::sleep(Duration::from_secs(60));
-      continue;
+    if *shutdown_rx.borrow_and_update() {
+      println!("Polling stopped");
+      return pending_alerts;
+    }
+
+    let tickers: Vec<(String, u32)> = stock_data_map.iter()
+      .map(|(ticker, stock_data)| (ticker.clone(), stock_data.config.timeframe))
+      .collect();
+
+    let wake = match scheduler.next_wake(&tickers, clock.now()) {
+      Some(wake) => wake,
+      None => {
+        // Nothing to poll until the watchlist gets a ticker
+        tokio::select! {
+          Ok(()) = watchlist_rx.changed() => {
+            let next_watchlist = watchlist_rx.borrow_and_update().clone();
+            apply_watchlist_changes(stock_data_map, &next_watchlist, database.as_ref()).await;
+          }
+          _ = shutdown_rx.changed() => {}
+        }
+        continue;
+      }
+    };
+
+    // Sleep until the next candle closes, unless the watchlist is reloaded or we are shutting down first
+    tokio::select! {
+      _ = clock.sleep_until(wake.wake_at) => {}
+      Ok(()) = watchlist_rx.changed() => {
+        let next_watchlist = watchlist_rx.borrow_and_update().clone();
+        apply_watchlist_changes(stock_data_map, &next_watchlist, database.as_ref()).await;
+        continue;
+      }
+      _ = shutdown_rx.changed() => continue,
     }
 
+    // Each ticker follows its own asset class's calendar, so crypto keeps polling while equities are closed.
+    // Pre-market and after-hours candles are only fetched for tickers that opted in
+    let due_tickers: Vec<&String> = wake.due_tickers.iter()
+      .filter(|ticker| stock_data_map.get(*ticker).is_some_and(|stock_data| stock_data.is_polling_candle(wake.candle_close)))
+      .collect();
+
+    if due_tickers.is_empty() {
+      if let Some(next_wake) = scheduler.next_wake(&tickers, clock.now()) {
+        let wait = next_wake.sleep_duration(clock.now());
+        println!("Waiting for market to open, next check at {} in {}s", next_wake.wake_at, wait.as_secs());
+      }
+      continue;
+    }
 
-    for ticker in &keys {
-      
-      let stock_data =  stock_data_map.get_mut(ticker).unwrap();
-      let polygon_data = fetch_data(&ticker, &timeframe, &timestamp_from, &timestamp_to, &api_key).await;
+    // Stop just short of the candle close, the candle starting there is still forming
+    let timestamp_to: i64 = wake.candle_close.timestamp_millis() - 1;
 
+    // A failed fetch is not retried until the next candle, whose window reaches back and fills the gap
+    let mut in_flight = dispatch_fetches(stock_data_map, &due_tickers, timestamp_to, &provider, &polling_config);
 
-      stock_data.add_stock_data(&polygon_data.unwrap());
-      stock_data.maybe_evict_if_over_cap();
+    // Results are applied in the order they arrive, so one slow ticker does not hold up the others' alerts
+    while let Some(joined) = in_flight.join_next().await {
+      let (ticker, polygon_data) = match joined {
+        Ok(fetched) => fetched,
+        Err(e) => {
+          eprintln!("Fetch task failed: {}", e);
+          continue;
+        }
+      };
 
-      // match stock_data.pop_front_if_at_capacity() {
-      //     // TODO create a method that adds stock data to a database. 
-      //     Some(data)  => {},
-      //     None =>  {},
-      // };
+      let stock_data = match stock_data_map.get_mut(&ticker) {
+        Some(stock_data) => stock_data,
+        None => continue,
+      };
 
-      
-      if stock_data.analyze(now_hour, now_min).is_alert_fireable(){
-        let email_res = send_email().await;
-        if let Err(e) = email_res {
-            eprintln!("Failed to send email {}", e);
+      let price_data = match polygon_data {
+        Ok(price_data) => price_data,
+        Err(FetchError::EmptyResults) => {
+          println!("No new candles for {}", display_name(&ticker));
+          continue;
+        }
+        Err(e) => {
+          eprintln!("Skipping {} this cycle, {} {}", display_name(&ticker), provider.name(), e);
+          continue;
         }
+      };
+
+      let added = stock_data.add_new_stock_data(&price_data);
+      if added == 0 {
+        println!("No new candles for {}", display_name(&ticker));
+        continue;
       }
 
-      prev_fetched_min  = now_min - now_min % timeframe;
+      let alert_cluster = stock_data.analyze(wake.candle_close);
+      if alert_cluster.is_alert_fireable(){
+        let candle_timestamp = stock_data.last_timestamp().unwrap_or_default();
+        for alert_type in alert_cluster.fired_alert_types() {
+          let alert = AlertRecord {
+            ticker: ticker.clone(),
+            alert_type: alert_type.to_string(),
+            candle_timestamp,
+            fired_at: clock.now().timestamp_millis(),
+            indicators: alert_cluster.indicators,
+          };
 
+          if let Err(e) = notifier.notify(&alert).await {
+            eprintln!("Failed to send {} alert for {}: {}", alert.alert_type, display_name(&ticker), e);
+          }
+          pending_alerts.push(alert);
+        }
+      }
 
-        // TODO remove when confirmed
-      for row in stock_data.stock_data.iter(){
-        println!("ticker: {:?}, close: {:?}, open: {:?}, high:  {:?}, low: {:?} \n", ticker, row.close, row.open, row.high, row.low);
+      // Saved after alerting so a slow write does not delay the email. On failure the watermark stays put,
+      // and the candles are retried with the next poll's. Alerts stay pending until they are written the same way
+      if let Some(repositories) = &database {
+        if let Err(e) = flush_stock_data(stock_data, repositories.candles.as_ref()).await {
+          eprintln!("Failed to save candles for {}, retrying next poll: {}", display_name(&ticker), e);
+        }
+        if let Err(e) = flush_alerts(&mut pending_alerts, repositories.alerts.as_ref()).await {
+          eprintln!("Failed to save {} alerts, retrying next poll: {}", pending_alerts.len(), e);
+        }
       }
+    }
+  }
+}
+
+/**
+ * Starts a fetch for every due ticker at once
+ *
+ * At most max_concurrent_fetches requests are in flight, on top of the provider's own rate limiter,
+ * and a fetch that has not finished within fetch_timeout_secs is abandoned for this cycle.
+ *
+ * @return a JoinSet yielding (ticker, fetch result) as each fetch completes
+ */
+fn dispatch_fetches(
+  stock_data_map: &HashMap<String, StockData<'_>>,
+  due_tickers: &[&String],
+  timestamp_to: i64,
+  provider: &Arc<dyn MarketDataProvider>,
+  polling_config: &PollingConfig,
+) -> JoinSet<(String, Result<Vec<PriceDatum>, FetchError>)> {
+  let permits = Arc::new(Semaphore::new(polling_config.max_concurrent_fetches));
+  let fetch_timeout = Duration::from_secs(polling_config.fetch_timeout_secs);
+  let mut in_flight = JoinSet::new();
+
+  for ticker in due_tickers {
+    let stock_data = &stock_data_map[*ticker];
+    let ticker = (*ticker).clone();
+    let timeframe = stock_data.config.timeframe;
+    let timestamp_from = stock_data.fetch_window_start(timestamp_to);
+    let is_first_poll = stock_data.last_timestamp().is_none();
+    let provider = Arc::clone(provider);
+    let permits = Arc::clone(&permits);
+
+    in_flight.spawn(async move {
+      let _permit = permits.acquire_owned().await.expect("fetch semaphore is never closed");
+      // Nothing held yet means there are no missed candles to fill in, only the newest one is needed
+      let fetch = async {
+        if is_first_poll {
+          return provider.fetch_latest_bar(&ticker, timeframe, timestamp_to).await.map(|bar| vec![bar]);
+        }
+        return provider.fetch_bars(&ticker, timeframe, timestamp_from, timestamp_to).await;
+      };
+      let polygon_data = match timeout(fetch_timeout, fetch).await {
+        Ok(polygon_data) => polygon_data,
+        Err(_) => Err(FetchError::Timeout(fetch_timeout)),
+      };
+      (ticker, polygon_data)
+    });
+  }
 
+  in_flight
+}
 
+/**
+ * Brings the polled tickers in line with a newly loaded watchlist
+ *
+ * Added tickers get a fresh StockData, removed tickers are flushed and dropped,
+ * and updated tickers keep their candles but take on the new settings.
+ */
+async fn apply_watchlist_changes(
+  stock_data_map: &mut HashMap<String, StockData<'_>>,
+  next_watchlist: &[TickerConfig],
+  database: Option<&Repositories>,
+) {
+  let current_watchlist: Vec<TickerConfig> = stock_data_map.values().map(|stock_data| stock_data.config.clone()).collect();
+  let diff = WatchlistDiff::between(&current_watchlist, next_watchlist);
+  println!("{}", diff);
+
+  for ticker_config in &diff.added {
+    stock_data_map.insert(ticker_config.symbol.clone(), setup_ticker_stock_data(ticker_config, database).await);
+  }
+
+  for symbol in &diff.removed {
+    if let Some(mut stock_data) = stock_data_map.remove(symbol) && let Some(repositories) = database {
+      match flush_stock_data(&mut stock_data, repositories.candles.as_ref()).await {
+        Ok(written) => println!("Flushed {} candles for {}", written, symbol),
+        Err(e) => eprintln!("Failed to flush candles for {}, dropping them: {}", symbol, e),
+      }
+    }
+  }
+
+  for ticker_config in &diff.updated {
+    if let Some(stock_data) = stock_data_map.get_mut(&ticker_config.symbol) {
+      stock_data.config = ticker_config.clone();
+      while stock_data.maybe_evict_if_over_cap().is_some() {}
     }
   }
 }
 
-fn is_market_closed(now: &chrono::DateTime<Local>)-> bool{
-  now.hour() * 60 + now.minute() < 390 || now.hour() > 13
+#[cfg(test)]
+mod tests {
+  use std::sync::Mutex;
+
+  use async_trait::async_trait;
+  use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+
+  use super::*;
+  use crate::{
+    clock::simulated_clock::SimulatedClock,
+    notifier::captured_notifier::CapturedNotifier,
+    market_calendar::session::Session,
+    market_data::market_data_provider::RateLimitHints,
+    test_fixtures::ticker_config,
+  };
+
+  /**
+   * Hands back the candle that just closed for every request, and shuts the loop down after the closing bell
+   *
+   * @field closing_volume: volume of the candle closing at the bell, every other candle trades 1,000
+   */
+  struct OneCandleProvider {
+    closing_bell: DateTime<Utc>,
+    closing_volume: u64,
+    shutdown_tx: watch::Sender<bool>,
+    requested_to: Mutex<Vec<i64>>,
+  }
+
+  #[async_trait]
+  impl MarketDataProvider for OneCandleProvider {
+    fn name(&self) -> &str {
+      "one candle"
+    }
+
+    fn rate_limit_hints(&self) -> RateLimitHints {
+      RateLimitHints { requests_per_minute: 1000, burst: 1000 }
+    }
+
+    async fn fetch_bars(&self, _ticker: &str, timeframe: u32, _timestamp_from: i64, timestamp_to: i64) -> Result<Vec<PriceDatum>, FetchError> {
+      self.requested_to.lock().unwrap().push(timestamp_to);
+
+      let candle_close = timestamp_to + 1;
+      let mut volume = 1_000;
+      if candle_close >= self.closing_bell.timestamp_millis() {
+        volume = self.closing_volume;
+        let _ = self.shutdown_tx.send(true);
+      }
+
+      Ok(vec![PriceDatum {
+        open: 100.0,
+        high: 101.0,
+        low: 99.0,
+        close: 100.5,
+        volume,
+        timestamp: (candle_close - timeframe as i64 * 60 * 1000) as u64,
+        vwap: None,
+        trade_count: None,
+      }])
+    }
+  }
+
+  /**
+   * Moves a manual clock on one candle at a time, each time the loop has gone back to sleep on it
+   */
+  async fn advance_each_candle(clock: &SimulatedClock, timeframe: TimeDelta) {
+    loop {
+      while clock.sleepers() == 0 {
+        tokio::task::yield_now().await;
+      }
+      clock.advance(timeframe);
+      // Hand over to the woken loop, which drops its wait before the driver looks again
+      tokio::task::yield_now().await;
+    }
+  }
+
+  #[tokio::test]
+  async fn polls_every_regular_candle_of_a_trading_day() {
+    // Monday 14 July 2025, 08:00 to 16:00 New York time, starting on a settle delay so each step lands on a wake
+    let clock = Arc::new(SimulatedClock::new(Utc.with_ymd_and_hms(2025, 7, 14, 12, 0, 5).unwrap()));
+    let closing_bell = Utc.with_ymd_and_hms(2025, 7, 14, 20, 0, 0).unwrap();
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let (_watchlist_tx, watchlist_rx) = watch::channel(vec![ticker_config("QQQ")]);
+    let provider = Arc::new(OneCandleProvider { closing_bell, closing_volume: 1_000, shutdown_tx, requested_to: Mutex::new(Vec::new()) });
+
+    let mut stock_data_map = HashMap::new();
+    stock_data_map.insert("QQQ".to_string(), StockData::new(ticker_config("QQQ")));
+    let repositories = Repositories::in_memory();
+
+    let polling = monitor_stock_data(
+      &mut stock_data_map,
+      watchlist_rx,
+      shutdown_rx,
+      PollingContext {
+        provider: provider.clone(),
+        polling_config: PollingConfig::default(),
+        clock: clock.clone(),
+        notifier: Arc::new(CapturedNotifier::new()),
+        database: Some(repositories.clone()),
+      },
+    );
+    // Biased so the loop always sees a move of the clock before the driver looks for sleepers again
+    let pending_alerts = tokio::select! {
+      biased;
+      pending_alerts = polling => pending_alerts,
+      _ = advance_each_candle(&clock, TimeDelta::minutes(15)) => unreachable!("the clock is moved until polling stops"),
+    };
+
+    // 9:45 through 16:00, one request per candle, nothing before the open
+    let requested_to = provider.requested_to.lock().unwrap().clone();
+    assert_eq!(requested_to.len(), 26);
+    assert_eq!(requested_to[0], Utc.with_ymd_and_hms(2025, 7, 14, 13, 45, 0).unwrap().timestamp_millis() - 1);
+    assert_eq!(*requested_to.last().unwrap(), closing_bell.timestamp_millis() - 1);
+
+    let stock_data = &stock_data_map["QQQ"];
+    assert_eq!(stock_data.stock_data.len(), 26);
+    assert!(stock_data.stock_data.iter().all(|datum| datum.session == Session::Regular));
+    assert_eq!(stock_data.stock_data[0].timestamp, Utc.with_ymd_and_hms(2025, 7, 14, 13, 30, 0).unwrap().timestamp_millis() as u64);
+
+    // Every candle was saved as it arrived
+    let saved = repositories.candles.latest_candles("QQQ", 15, &Session::ALL, 100).await.unwrap();
+    assert_eq!(saved.len(), 26);
+    assert_eq!(stock_data.persisted_until, Some(saved[25].timestamp as u64));
+
+    // Flat volume never spikes, and the loop stopped at the settle delay after the bell
+    assert!(pending_alerts.is_empty());
+    assert_eq!(clock.now(), closing_bell + TimeDelta::seconds(5));
+  }
+
+  #[tokio::test]
+  async fn alerts_are_saved_with_the_poll_that_fired_them() {
+    let clock = Arc::new(SimulatedClock::auto_advancing(Utc.with_ymd_and_hms(2025, 7, 14, 12, 0, 0).unwrap()));
+    let closing_bell = Utc.with_ymd_and_hms(2025, 7, 14, 20, 0, 0).unwrap();
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let (_watchlist_tx, watchlist_rx) = watch::channel(vec![ticker_config("QQQ")]);
+    // The last candle of the day spikes inside the before close window
+    let provider = Arc::new(OneCandleProvider { closing_bell, closing_volume: 50_000, shutdown_tx, requested_to: Mutex::new(Vec::new()) });
+
+    let mut stock_data_map = HashMap::new();
+    stock_data_map.insert("QQQ".to_string(), StockData::new(ticker_config("QQQ")));
+    let repositories = Repositories::in_memory();
+
+    let pending_alerts = monitor_stock_data(
+      &mut stock_data_map,
+      watchlist_rx,
+      shutdown_rx,
+      PollingContext {
+        provider,
+        polling_config: PollingConfig::default(),
+        clock,
+        notifier: Arc::new(CapturedNotifier::new()),
+        database: Some(repositories.clone()),
+      },
+    ).await;
+
+    // Already written when polling stopped, nothing is left for the shutdown flush
+    assert!(pending_alerts.is_empty());
+    let saved = repositories.alerts.recent_alerts(Some("QQQ"), 10).await.unwrap();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].alert_type, "volume_spike");
+    assert_eq!(saved[0].candle_timestamp, Utc.with_ymd_and_hms(2025, 7, 14, 19, 45, 0).unwrap().timestamp_millis());
+  }
 }
@@ -1,28 +1,155 @@
 use std::collections::HashMap;
 
-use crate::{data_polling::constants::{IS_DB_ENABLED, QUEUE_CAPACITY}, polygon_api::stock::StockData};
+use crate::{
+  config::monitor_config::TickerConfig,
+  database::{candle_repository::CandleRepository, database_error::DatabaseError, repositories::Repositories},
+  indicators::indicator_set::IndicatorSet,
+  market_data::asset_class::display_name,
+  polygon_api::stock::{StockData, StockDatum},
+};
 
 
 /**
  * On startup, gets any cache of stock data
  */
-pub fn setup_stock_data<'a>(tickers:  &'a Vec<&'a str>)-> HashMap<String, StockData<'a> >{
+pub async fn setup_stock_data<'a>(tickers: &[TickerConfig], database: Option<&Repositories>)-> HashMap<String, StockData<'a> >{
 
 
   let mut res: HashMap<String, StockData<'a>> = HashMap::new();
 
 
-  tickers.iter().for_each(|ticker | {
-    let name = String::from(ticker.clone());
-    let _ = &res.insert(ticker.to_string(), StockData::new(name, QUEUE_CAPACITY));
+  for ticker_config in tickers {
+    res.insert(ticker_config.symbol.clone(), setup_ticker_stock_data(ticker_config, database).await);
+  }
 
-    //Populate with DB values
-    if (IS_DB_ENABLED){
-       println!("{:?}",  "meow"); 
 
-    }   
+  return res;
+ } 
+
+/**
+ * Creates the StockData for a single ticker, used on startup and when a ticker is added to the watchlist.
+ * The newest queue_capacity candles saved for the ticker in the sessions it polls are loaded back in,
+ * so volume spikes can be judged from the first poll. At least IndicatorSet::WARM_UP_CANDLES are read so the
+ * indicators continue where they were, only the newest queue_capacity are kept.
+ * Without a database, or if they cannot be read, the ticker starts empty.
+ */
+pub async fn setup_ticker_stock_data<'a>(ticker_config: &TickerConfig, database: Option<&Repositories>) -> StockData<'a> {
+  let mut stock_data = StockData::new(ticker_config.clone());
+
+  //Populate with DB values
+  if let Some(repositories) = database {
+    match load_saved_stock_data(ticker_config, repositories.candles.as_ref()).await {
+      Ok(saved_data) => {
+        stock_data.restore_stock_data(saved_data);
+        println!("Loaded {} saved candles for {}", stock_data.stock_data.len(), display_name(&ticker_config.symbol));
+      }
+      Err(e) => eprintln!("Could not load saved candles for {}, starting empty: {}", display_name(&ticker_config.symbol), e),
+    }
+  }
+
+  return stock_data;
+}
+
+async fn load_saved_stock_data(ticker_config: &TickerConfig, candles: &dyn CandleRepository) -> Result<Vec<StockDatum>, DatabaseError> {
+  let stocks = candles
+    .latest_candles(
+      &ticker_config.symbol,
+      ticker_config.timeframe,
+      &ticker_config.polled_sessions(),
+      (ticker_config.queue_capacity as usize).max(IndicatorSet::WARM_UP_CANDLES),
+    )
+    .await?;
+
+  // A row that cannot be read is skipped rather than guessed at, the rest still warm the ticker up
+  let saved_data = stocks.iter().filter_map(|stock| match StockDatum::from_stock(stock) {
+    Ok(datum) => Some(datum),
+    Err(e) => {
+      eprintln!("Skipped saved {} candle at {}: {}", display_name(&ticker_config.symbol), stock.timestamp, e);
+      None
+    }
   });
+  return Ok(saved_data.collect());
+}
 
+#[cfg(test)]
+mod tests {
+  use crate::{
+    data_polling::flush_stock_data::flush_stock_data,
+    database::models::NewStock,
+    market_calendar::session::Session,
+    polygon_api::stock::StockData,
+    test_fixtures::{assert_indicators_close, bar, saved_candle, ticker_config, wavy_closes},
+  };
 
-  return res;
- } 
\ No newline at end of file
+  use super::*;
+
+  #[tokio::test]
+  async fn warm_starts_from_the_newest_saved_candles_of_the_timeframe() {
+    let repositories = Repositories::in_memory();
+    let mut candles: Vec<NewStock> = (1..=5).map(|timestamp| saved_candle(15, timestamp)).collect();
+    candles.push(saved_candle(5, 6));
+    repositories.candles.save_candles(candles).await.unwrap();
+
+    let ticker_config = TickerConfig { queue_capacity: 3, ..ticker_config("QQQ") };
+    let stock_data = setup_ticker_stock_data(&ticker_config, Some(&repositories)).await;
+
+    assert_eq!(stock_data.stock_data.iter().map(|datum| datum.timestamp).collect::<Vec<_>>(), vec![3, 4, 5]);
+    assert_eq!(stock_data.persisted_until, Some(5));
+  }
+
+  #[tokio::test]
+  async fn candles_outside_the_polled_sessions_do_not_count_towards_the_queue() {
+    let repositories = Repositories::in_memory();
+    let mut candles: Vec<NewStock> = (1..=3).map(|timestamp| saved_candle(15, timestamp)).collect();
+    candles.extend((4..=5).map(|timestamp| NewStock { session: "after_hours".to_string(), ..saved_candle(15, timestamp) }));
+    candles.push(NewStock { session: "overnight".to_string(), ..saved_candle(15, 6) });
+    repositories.candles.save_candles(candles).await.unwrap();
+
+    let regular_only = TickerConfig { queue_capacity: 3, ..ticker_config("QQQ") };
+    let stock_data = setup_ticker_stock_data(&regular_only, Some(&repositories)).await;
+    assert_eq!(stock_data.stock_data.iter().map(|datum| datum.timestamp).collect::<Vec<_>>(), vec![1, 2, 3]);
+
+    // The overnight row is skipped instead of being read as a regular hours candle
+    let extended_hours = TickerConfig { queue_capacity: 3, extended_hours: true, ..ticker_config("QQQ") };
+    let stock_data = setup_ticker_stock_data(&extended_hours, Some(&repositories)).await;
+    assert_eq!(stock_data.stock_data.iter().map(|datum| datum.timestamp).collect::<Vec<_>>(), vec![3, 4, 5]);
+  }
+
+  #[tokio::test]
+  async fn recomputes_emas_saved_while_warming_up() {
+    let repositories = Repositories::in_memory();
+    let candles = (1..=9).map(|timestamp| NewStock { ema_9: None, ema_20: None, ..saved_candle(15, timestamp) }).collect();
+    repositories.candles.save_candles(candles).await.unwrap();
+
+    let stock_data = setup_ticker_stock_data(&ticker_config("QQQ"), Some(&repositories)).await;
+
+    assert_eq!(stock_data.indicators.values().ema_9, Some(100.0));
+    assert_eq!(stock_data.indicators.values().ema_20, None);
+  }
+
+  #[tokio::test]
+  async fn indicators_continue_after_a_restart_even_with_a_small_queue() {
+    let repositories = Repositories::in_memory();
+    let ticker_config = TickerConfig { queue_capacity: 2, ..ticker_config("QQQ") };
+    let closes = wavy_closes(80);
+
+    // Saved every poll, so every candle reaches the database although only two stay in memory
+    let mut running = StockData::new(ticker_config.clone());
+    for (timestamp, close) in closes[..60].iter().enumerate() {
+      running.add_stock_data(&bar(timestamp as u64, *close), Session::Regular);
+      running.maybe_evict_if_over_cap();
+      flush_stock_data(&mut running, repositories.candles.as_ref()).await.unwrap();
+    }
+
+    let mut restarted = setup_ticker_stock_data(&ticker_config, Some(&repositories)).await;
+    assert_eq!(restarted.stock_data.len(), 2);
+    assert_indicators_close(&restarted.indicators.values(), &running.indicators.values());
+    assert!(restarted.indicators.values().macd.is_some() && restarted.indicators.values().bollinger.is_some());
+
+    for (timestamp, close) in closes.iter().enumerate().skip(60) {
+      restarted.add_stock_data(&bar(timestamp as u64, *close), Session::Regular);
+      running.add_stock_data(&bar(timestamp as u64, *close), Session::Regular);
+      assert_indicators_close(&restarted.stock_data.back().unwrap().indicators, &running.stock_data.back().unwrap().indicators);
+    }
+  }
+}
@@ -0,0 +1,109 @@
+use std::fmt;
+
+use crate::config::monitor_config::TickerConfig;
+
+/**
+ * The difference between the watchlist currently being polled and a newly loaded one
+ *
+ * @added: tickers that are only in the new watchlist
+ * @removed: symbols that are only in the current watchlist
+ * @updated: tickers in both watchlists whose settings changed, with their new settings
+ */
+#[derive(Debug, Default)]
+pub struct WatchlistDiff {
+    pub added: Vec<TickerConfig>,
+    pub removed: Vec<String>,
+    pub updated: Vec<TickerConfig>,
+}
+
+impl WatchlistDiff {
+    pub fn between(current: &[TickerConfig], next: &[TickerConfig]) -> WatchlistDiff {
+        let mut diff = WatchlistDiff::default();
+
+        for ticker in next {
+            match current.iter().find(|existing| existing.symbol == ticker.symbol) {
+                None => diff.added.push(ticker.clone()),
+                Some(existing) if existing != ticker => diff.updated.push(ticker.clone()),
+                Some(_) => {}
+            }
+        }
+
+        for existing in current {
+            if !next.iter().any(|ticker| ticker.symbol == existing.symbol) {
+                diff.removed.push(existing.symbol.clone());
+            }
+        }
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+}
+
+impl fmt::Display for WatchlistDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "watchlist unchanged");
+        }
+
+        let symbols = |tickers: &[TickerConfig]| -> String {
+            tickers.iter().map(|ticker| ticker.symbol.as_str()).collect::<Vec<&str>>().join(", ")
+        };
+
+        write!(
+            f,
+            "watchlist changed, added: [{}], removed: [{}], updated: [{}]",
+            symbols(&self.added),
+            self.removed.join(", "),
+            symbols(&self.updated)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_fixtures::ticker_config;
+
+    use super::*;
+
+    #[test]
+    fn sorts_tickers_into_added_removed_and_updated() {
+        let current = vec![ticker_config("QQQ"), ticker_config("NVDA"), ticker_config("SPY")];
+        let mut retuned_nvda = ticker_config("NVDA");
+        retuned_nvda.volume_spike_k = 2.5;
+        let next = vec![ticker_config("QQQ"), retuned_nvda.clone(), ticker_config("TSLA")];
+
+        let diff = WatchlistDiff::between(&current, &next);
+
+        assert_eq!(diff.added, vec![ticker_config("TSLA")]);
+        assert_eq!(diff.removed, vec!["SPY".to_string()]);
+        assert_eq!(diff.updated, vec![retuned_nvda]);
+        assert_eq!(diff.to_string(), "watchlist changed, added: [TSLA], removed: [SPY], updated: [NVDA]");
+    }
+
+    #[test]
+    fn the_same_watchlist_in_another_order_is_unchanged() {
+        let current = vec![ticker_config("QQQ"), ticker_config("NVDA")];
+        let next = vec![ticker_config("NVDA"), ticker_config("QQQ")];
+
+        let diff = WatchlistDiff::between(&current, &next);
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "watchlist unchanged");
+    }
+
+    #[test]
+    fn an_empty_watchlist_removes_everything() {
+        let current = vec![ticker_config("QQQ"), ticker_config("NVDA")];
+
+        let emptied = WatchlistDiff::between(&current, &[]);
+        assert_eq!(emptied.removed, vec!["QQQ".to_string(), "NVDA".to_string()]);
+        assert!(emptied.added.is_empty() && emptied.updated.is_empty());
+
+        let refilled = WatchlistDiff::between(&[], &current);
+        assert_eq!(refilled.added, current);
+        assert!(refilled.removed.is_empty());
+    }
+}
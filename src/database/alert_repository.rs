@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+
+use crate::database::{
+    database_error::DatabaseError,
+    models::{Alert, NewAlert},
+};
+
+/**
+ * Where fired alerts are recorded, Postgres when running and in memory in tests
+ */
+#[async_trait]
+pub trait AlertRepository: Send + Sync {
+    /**
+     * @return how many alerts were written
+     */
+    async fn save_alerts(&self, alerts: Vec<NewAlert>) -> Result<usize, DatabaseError>;
+
+    /**
+     * The most recently fired alerts, for one ticker or all of them
+     *
+     * @return at most limit alerts, newest first
+     */
+    async fn recent_alerts(&self, ticker: Option<&str>, limit: usize) -> Result<Vec<Alert>, DatabaseError>;
+}
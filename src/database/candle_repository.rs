@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+
+use crate::{
+    database::{
+        database_error::DatabaseError,
+        models::{NewStock, Stock},
+    },
+    market_calendar::session::Session,
+};
+
+/**
+ * Where candles are saved and read back, Postgres when running and in memory in tests
+ */
+#[async_trait]
+pub trait CandleRepository: Send + Sync {
+    /**
+     * Saves candles, replacing any already saved for the same ticker, timeframe and timestamp
+     *
+     * @return how many rows were written
+     */
+    async fn save_candles(&self, candles: Vec<NewStock>) -> Result<usize, DatabaseError>;
+
+    /**
+     * A ticker's newest candles of one timeframe
+     *
+     * @param sessions: only candles from these sessions are read and count towards limit
+     * @return at most limit candles, oldest first
+     */
+    async fn latest_candles(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        sessions: &[Session],
+        limit: usize,
+    ) -> Result<Vec<Stock>, DatabaseError>;
+
+    /**
+     * A ticker's candles of one timeframe between two timestamps, inclusive
+     *
+     * @return the candles oldest first
+     */
+    async fn candles_between(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        timestamp_from: i64,
+        timestamp_to: i64,
+    ) -> Result<Vec<Stock>, DatabaseError>;
+
+    /**
+     * Every ticker with candles of one timeframe saved
+     *
+     * @return each ticker with the timestamp of its oldest candle
+     */
+    async fn oldest_candles(&self, timeframe: u32) -> Result<Vec<(String, i64)>, DatabaseError>;
+
+    /**
+     * Deletes a ticker's candles of one timeframe older than a timestamp
+     *
+     * @param before: candles with an earlier timestamp are deleted, a candle at exactly this timestamp is kept
+     * @return how many rows were deleted
+     */
+    async fn delete_candles_before(&self, ticker: &str, timeframe: u32, before: i64) -> Result<usize, DatabaseError>;
+
+    /**
+     * Whether the store can currently be read
+     */
+    async fn check_health(&self) -> Result<(), DatabaseError>;
+}
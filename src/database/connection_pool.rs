@@ -0,0 +1,253 @@
+use std::{env, fmt, sync::{Arc, Mutex}, time::Duration};
+
+use diesel::{
+    prelude::*,
+    r2d2::{self, ConnectionManager, CustomizeConnection, Pool, R2D2Connection},
+};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use dotenv::dotenv;
+use tokio::task::spawn_blocking;
+
+use crate::database::{database_error::DatabaseError, repositories::Repositories};
+
+pub type PgPool = DatabasePool<PgConnection>;
+pub type SqlitePool = DatabasePool<SqliteConnection>;
+
+const PG_MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/postgres");
+const SQLITE_MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/sqlite");
+
+const MAX_CONNECTIONS: u32 = 8;
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/**
+ * Opens the database at DATABASE_URL and returns the repositories over it
+ *
+ * postgres:// and postgresql:// URLs use Postgres, sqlite://path uses a SQLite file at path, created if missing.
+ * No connection is opened up front, so a database that is down at startup is used as soon as it answers.
+ * Pending migrations run on the first connection that succeeds. Every connection is checked with a cheap
+ * query when it is taken from the pool, so one dropped by the server is replaced instead of failing the caller's query.
+ *
+ * @return an error only when DATABASE_URL is missing or not a supported URL
+ */
+pub async fn connect_database() -> Result<Repositories, DatabaseError> {
+    dotenv().ok();
+    let database_url = env::var("DATABASE_URL").map_err(|_| DatabaseError::MissingUrl)?;
+
+    let repositories = if let Some(path) = database_url.strip_prefix("sqlite://") {
+        Repositories::sqlite(open_sqlite(path))
+    } else if database_url.starts_with("postgres://") || database_url.starts_with("postgresql://") {
+        let manager = ConnectionManager::<PgConnection>::new(database_url);
+        Repositories::postgres(open_pool(manager, || PG_MIGRATIONS, None))
+    } else {
+        let scheme = database_url.split("://").next().unwrap_or_default().to_string();
+        return Err(DatabaseError::UnsupportedUrl(scheme));
+    };
+
+    if let Err(e) = repositories.candles.check_health().await {
+        eprintln!("Database not ready yet, retrying on every write: {}", e);
+    }
+    Ok(repositories)
+}
+
+/**
+ * A SQLite pool on the file at path, migrated when its first connection opens
+ */
+pub fn open_sqlite(path: &str) -> SqlitePool {
+    let pragmas = "PRAGMA journal_mode = WAL; PRAGMA busy_timeout = 5000;";
+    open_pool(ConnectionManager::<SqliteConnection>::new(path), || SQLITE_MIGRATIONS, Some(pragmas))
+}
+
+/**
+ * A connection pool together with how its migrations went, so a failed migration is reported as one
+ * rather than as a connection that could not be opened
+ */
+pub struct DatabasePool<C: R2D2Connection + 'static> {
+    pool: Pool<ConnectionManager<C>>,
+    migration: Arc<Mutex<MigrationState>>,
+}
+
+impl<C: R2D2Connection + 'static> Clone for DatabasePool<C> {
+    fn clone(&self) -> Self {
+        DatabasePool {
+            pool: self.pool.clone(),
+            migration: Arc::clone(&self.migration),
+        }
+    }
+}
+
+/**
+ * Pending: no connection has opened yet to run them
+ * Applied: every migration ran, new connections skip them
+ * Failed: the last attempt failed with the message, the next connection tries again
+ */
+#[derive(Debug, Clone, PartialEq)]
+enum MigrationState {
+    Pending,
+    Applied,
+    Failed(String),
+}
+
+fn open_pool<C>(
+    manager: ConnectionManager<C>,
+    migrations: fn() -> EmbeddedMigrations,
+    pragmas: Option<&'static str>,
+) -> DatabasePool<C>
+where
+    C: R2D2Connection + MigrationHarness<C::Backend> + 'static,
+{
+    let migration = Arc::new(Mutex::new(MigrationState::Pending));
+    let setup = ConnectionSetup {
+        pragmas,
+        migrations,
+        migration: Arc::clone(&migration),
+    };
+
+    let pool = Pool::builder()
+        .max_size(MAX_CONNECTIONS)
+        .connection_timeout(CONNECTION_TIMEOUT)
+        .test_on_check_out(true)
+        .connection_customizer(Box::new(setup))
+        .build_unchecked(manager);
+
+    DatabasePool { pool, migration }
+}
+
+/**
+ * Prepares every connection the pool opens
+ *
+ * @field pragmas: statements run on each new connection, e.g. SQLite's journal mode
+ * @field migrations: the backend's embedded migrations, which can only be run by value
+ * @field migration: shared with the pool, only connections opened before the migrations succeed run them
+ */
+struct ConnectionSetup {
+    pragmas: Option<&'static str>,
+    migrations: fn() -> EmbeddedMigrations,
+    migration: Arc<Mutex<MigrationState>>,
+}
+
+impl fmt::Debug for ConnectionSetup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectionSetup")
+            .field("pragmas", &self.pragmas)
+            .field("migration", &self.migration)
+            .finish()
+    }
+}
+
+impl<C> CustomizeConnection<C, r2d2::Error> for ConnectionSetup
+where
+    C: R2D2Connection + MigrationHarness<C::Backend> + 'static,
+{
+    fn on_acquire(&self, conn: &mut C) -> Result<(), r2d2::Error> {
+        if let Some(pragmas) = self.pragmas {
+            conn.batch_execute(pragmas).map_err(r2d2::Error::QueryError)?;
+        }
+
+        // Held while migrating, so a second connection opening at the same time waits instead of migrating too
+        let mut migration = self.migration.lock().unwrap();
+        if *migration != MigrationState::Applied {
+            match conn.run_pending_migrations((self.migrations)()) {
+                Ok(applied) => {
+                    for migration in applied {
+                        println!("Applied migration {}", migration);
+                    }
+                    *migration = MigrationState::Applied;
+                }
+                Err(e) => {
+                    *migration = MigrationState::Failed(e.to_string());
+                    // r2d2 only takes its own error type, the pool keeps the real reason in migration
+                    return Err(r2d2::Error::QueryError(diesel::result::Error::QueryBuilderError(e)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * Runs blocking diesel work on a pooled connection without holding up the async runtime
+ *
+ * @param: work: the queries to run, on a connection checked out for just this call
+ * @return DatabaseError::Migration when no connection could be handed out because the migrations keep failing
+ */
+pub async fn with_connection<C, T, F>(pool: &DatabasePool<C>, work: F) -> Result<T, DatabaseError>
+where
+    C: R2D2Connection + 'static,
+    T: Send + 'static,
+    F: FnOnce(&mut C) -> QueryResult<T> + Send + 'static,
+{
+    let pool = pool.clone();
+    spawn_blocking(move || {
+        let mut conn = pool.pool.get().map_err(|e| match &*pool.migration.lock().unwrap() {
+            MigrationState::Failed(message) => DatabaseError::Migration(message.clone()),
+            _ => DatabaseError::Pool(e),
+        })?;
+        Ok(work(&mut conn)?)
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use diesel::sql_query;
+
+    use crate::{market_calendar::session::Session, schema::stocks};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn a_database_missing_at_startup_is_migrated_once_it_appears() {
+        let dir = env::temp_dir().join(format!("stock_monitor_lazy_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("monitor.db");
+
+        // The directory does not exist yet, so no connection could open, but the pool is there to retry with
+        let repositories = Repositories::sqlite(open_sqlite(path.to_str().unwrap()));
+
+        fs::create_dir_all(&dir).unwrap();
+        repositories.candles.check_health().await.unwrap();
+        assert!(repositories.candles.latest_candles("QQQ", 15, &Session::ALL, 10).await.unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn a_failed_migration_is_reported_as_one() {
+        let path = env::temp_dir().join(format!("stock_monitor_bad_schema_{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        // A stocks table the monitor did not create, so the first migration cannot
+        let mut conn = SqliteConnection::establish(path.to_str().unwrap()).unwrap();
+        sql_query("CREATE TABLE stocks (symbol VARCHAR)").execute(&mut conn).unwrap();
+
+        let repositories = Repositories::sqlite(open_sqlite(path.to_str().unwrap()));
+
+        let error = repositories.candles.check_health().await.unwrap_err();
+        assert!(matches!(error, DatabaseError::Migration(_)), "{}", error);
+        assert!(error.to_string().starts_with("could not run database migrations: "), "{}", error);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn sqlite_upgrade_saves_warming_up_emas_as_null() {
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        // Every migration before the EMA columns became nullable
+        for _ in 0..4 {
+            conn.run_next_migration(SQLITE_MIGRATIONS).unwrap();
+        }
+        sql_query(
+            "INSERT INTO stocks (ticker, open, close, high, low, ema_9, timestamp, timeframe, ema_20)
+             VALUES ('QQQ', 100, 100, 100, 100, 0, 1, 15, 99.5)",
+        )
+        .execute(&mut conn)
+        .unwrap();
+
+        conn.run_pending_migrations(SQLITE_MIGRATIONS).unwrap();
+
+        let emas: (Option<f64>, Option<f64>) = stocks::table.select((stocks::ema_9, stocks::ema_20)).first(&mut conn).unwrap();
+        assert_eq!(emas, (None, Some(99.5)));
+    }
+}
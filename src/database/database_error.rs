@@ -0,0 +1,65 @@
+use std::{error::Error, fmt};
+
+/**
+ * Why the database could not be used
+ *
+ * MissingUrl: DATABASE_URL is not set
+ * UnsupportedUrl: DATABASE_URL is neither a postgres:// nor a sqlite:// URL
+ * Pool: no connection could be opened or checked out of the pool
+ * Migration: a pending migration failed to run
+ * Query: a statement failed
+ * Task: the blocking task running the query panicked or was cancelled
+ */
+#[derive(Debug)]
+pub enum DatabaseError {
+    MissingUrl,
+    UnsupportedUrl(String),
+    Pool(diesel::r2d2::PoolError),
+    Migration(String),
+    Query(diesel::result::Error),
+    Task(tokio::task::JoinError),
+}
+
+impl fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatabaseError::MissingUrl => write!(f, "DATABASE_URL is not set"),
+            DatabaseError::UnsupportedUrl(scheme) => {
+                write!(f, "DATABASE_URL scheme {:?} is not supported, use postgres:// or sqlite://", scheme)
+            }
+            DatabaseError::Pool(e) => write!(f, "could not get a database connection: {}", e),
+            DatabaseError::Migration(message) => write!(f, "could not run database migrations: {}", message),
+            DatabaseError::Query(e) => write!(f, "database query failed: {}", e),
+            DatabaseError::Task(e) => write!(f, "database task failed: {}", e),
+        }
+    }
+}
+
+impl Error for DatabaseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DatabaseError::Pool(e) => Some(e),
+            DatabaseError::Query(e) => Some(e),
+            DatabaseError::Task(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<diesel::r2d2::PoolError> for DatabaseError {
+    fn from(e: diesel::r2d2::PoolError) -> Self {
+        DatabaseError::Pool(e)
+    }
+}
+
+impl From<diesel::result::Error> for DatabaseError {
+    fn from(e: diesel::result::Error) -> Self {
+        DatabaseError::Query(e)
+    }
+}
+
+impl From<tokio::task::JoinError> for DatabaseError {
+    fn from(e: tokio::task::JoinError) -> Self {
+        DatabaseError::Task(e)
+    }
+}
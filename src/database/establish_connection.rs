@@ -1,12 +0,0 @@
-use diesel::prelude::*;
-use dotenv::dotenv;
-use std::env;
-
-
-pub fn establish_connection()-> PgConnection{
-  dotenv().ok();
-
-  let database_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set correctly");
-  PgConnection::establish(&database_url)
-  .unwrap_or_else(|_| panic!("Error connecting to {}", database_url))
-}
\ No newline at end of file
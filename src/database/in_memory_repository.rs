@@ -0,0 +1,189 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use async_trait::async_trait;
+use diesel::result::{DatabaseErrorKind, Error};
+
+use crate::{
+    database::{
+        alert_repository::AlertRepository,
+        candle_repository::CandleRepository,
+        database_error::DatabaseError,
+        models::{Alert, NewAlert, NewStock, Stock},
+    },
+    market_calendar::session::Session,
+};
+
+/**
+ * Candles and alerts held in memory with the same behaviour as Postgres, so tests run without a database
+ *
+ * @field unreachable: every call fails like a database that is down, until set back
+ */
+pub struct InMemoryRepository {
+    stocks: Mutex<Vec<Stock>>,
+    alerts: Mutex<Vec<Alert>>,
+    unreachable: AtomicBool,
+}
+
+impl InMemoryRepository {
+    pub fn new() -> Self {
+        InMemoryRepository {
+            stocks: Mutex::new(Vec::new()),
+            alerts: Mutex::new(Vec::new()),
+            unreachable: AtomicBool::new(false),
+        }
+    }
+
+    pub fn set_unreachable(&self, unreachable: bool) {
+        self.unreachable.store(unreachable, Ordering::SeqCst);
+    }
+
+    fn check_reachable(&self) -> Result<(), DatabaseError> {
+        if self.unreachable.load(Ordering::SeqCst) {
+            let message = Box::new("the in memory database is set unreachable".to_string());
+            return Err(DatabaseError::Query(Error::DatabaseError(DatabaseErrorKind::ClosedConnection, message)));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CandleRepository for InMemoryRepository {
+    async fn save_candles(&self, candles: Vec<NewStock>) -> Result<usize, DatabaseError> {
+        self.check_reachable()?;
+        let mut stocks = self.stocks.lock().unwrap();
+        let written = candles.len();
+
+        for candle in candles {
+            let existing = stocks.iter().position(|stock| {
+                stock.ticker == candle.ticker && stock.timeframe == candle.timeframe && stock.timestamp == candle.timestamp
+            });
+            let id = match existing {
+                Some(idx) => stocks.remove(idx).id,
+                None => stocks.iter().map(|stock| stock.id).max().unwrap_or(0) + 1,
+            };
+            stocks.push(candle.with_id(id));
+        }
+
+        stocks.sort_by_key(|stock| stock.timestamp);
+        Ok(written)
+    }
+
+    async fn latest_candles(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        sessions: &[Session],
+        limit: usize,
+    ) -> Result<Vec<Stock>, DatabaseError> {
+        self.check_reachable()?;
+        let stocks = self.stocks.lock().unwrap();
+        let matching: Vec<&Stock> = stocks
+            .iter()
+            .filter(|stock| stock.ticker == ticker && stock.timeframe == timeframe as i32)
+            .filter(|stock| sessions.iter().any(|session| session.as_str() == stock.session))
+            .collect();
+
+        Ok(matching[matching.len().saturating_sub(limit)..].iter().map(|stock| (*stock).clone()).collect())
+    }
+
+    async fn candles_between(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        timestamp_from: i64,
+        timestamp_to: i64,
+    ) -> Result<Vec<Stock>, DatabaseError> {
+        self.check_reachable()?;
+        let stocks = self.stocks.lock().unwrap();
+        Ok(stocks
+            .iter()
+            .filter(|stock| stock.ticker == ticker && stock.timeframe == timeframe as i32)
+            .filter(|stock| timestamp_from <= stock.timestamp && stock.timestamp <= timestamp_to)
+            .cloned()
+            .collect())
+    }
+
+    async fn oldest_candles(&self, timeframe: u32) -> Result<Vec<(String, i64)>, DatabaseError> {
+        self.check_reachable()?;
+        let stocks = self.stocks.lock().unwrap();
+        let mut oldest: Vec<(String, i64)> = Vec::new();
+
+        // Stocks are kept sorted by timestamp, so the first candle seen for a ticker is its oldest
+        for stock in stocks.iter().filter(|stock| stock.timeframe == timeframe as i32) {
+            if !oldest.iter().any(|(ticker, _)| *ticker == stock.ticker) {
+                oldest.push((stock.ticker.clone(), stock.timestamp));
+            }
+        }
+
+        Ok(oldest)
+    }
+
+    async fn delete_candles_before(&self, ticker: &str, timeframe: u32, before: i64) -> Result<usize, DatabaseError> {
+        self.check_reachable()?;
+        let mut stocks = self.stocks.lock().unwrap();
+        let count_before = stocks.len();
+        stocks.retain(|stock| !(stock.ticker == ticker && stock.timeframe == timeframe as i32 && stock.timestamp < before));
+
+        Ok(count_before - stocks.len())
+    }
+
+    async fn check_health(&self) -> Result<(), DatabaseError> {
+        self.check_reachable()
+    }
+}
+
+#[async_trait]
+impl AlertRepository for InMemoryRepository {
+    async fn save_alerts(&self, new_alerts: Vec<NewAlert>) -> Result<usize, DatabaseError> {
+        self.check_reachable()?;
+        let mut alerts = self.alerts.lock().unwrap();
+        let written = new_alerts.len();
+
+        for alert in new_alerts {
+            let id = alerts.len() as i32 + 1;
+            alerts.push(alert.with_id(id));
+        }
+
+        Ok(written)
+    }
+
+    async fn recent_alerts(&self, ticker: Option<&str>, limit: usize) -> Result<Vec<Alert>, DatabaseError> {
+        self.check_reachable()?;
+        let alerts = self.alerts.lock().unwrap();
+        let mut recent: Vec<Alert> = alerts
+            .iter()
+            .filter(|alert| ticker.is_none_or(|ticker| alert.ticker == ticker))
+            .cloned()
+            .collect();
+
+        recent.sort_by_key(|alert| std::cmp::Reverse(alert.fired_at));
+        recent.truncate(limit);
+        Ok(recent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_fixtures::fired_alert;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn recent_alerts_are_newest_first_and_limited() {
+        let repository = InMemoryRepository::new();
+        repository
+            .save_alerts(vec![fired_alert("QQQ", 2_000_000), fired_alert("NVDA", 3_000_000), fired_alert("QQQ", 1_000_000)])
+            .await
+            .unwrap();
+
+        let recent = repository.recent_alerts(None, 2).await.unwrap();
+        assert_eq!(recent.iter().map(|alert| alert.fired_at).collect::<Vec<_>>(), vec![3_000_000, 2_000_000]);
+
+        let qqq = repository.recent_alerts(Some("QQQ"), 10).await.unwrap();
+        assert_eq!(qqq.iter().map(|alert| (alert.id, alert.fired_at)).collect::<Vec<_>>(), vec![(1, 2_000_000), (3, 1_000_000)]);
+        assert!(repository.recent_alerts(Some("SPY"), 10).await.unwrap().is_empty());
+    }
+}
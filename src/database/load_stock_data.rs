@@ -0,0 +1,136 @@
+use diesel::{
+  connection::LoadConnection,
+  dsl::{self, AsSelect, Select},
+  helper_types,
+  prelude::*,
+  query_dsl::LoadQuery,
+};
+
+use crate::{
+  database::models::{Alert, Stock},
+  market_calendar::session::Session,
+  schema::{alerts, stocks},
+};
+
+// The queries are written once and run on both Postgres and SQLite, each function is generic over the connection
+
+#[dsl::auto_type]
+fn stocks_between<'a>(ticker: &'a str, timeframe: i32, timestamp_from: i64, timestamp_to: i64) -> _ {
+  stocks::table
+    .filter(stocks::ticker.eq(ticker))
+    .filter(stocks::timeframe.eq(timeframe))
+    .filter(stocks::timestamp.between(timestamp_from, timestamp_to))
+    .order(stocks::timestamp.asc())
+}
+
+#[dsl::auto_type]
+fn latest_stocks<'a>(ticker: &'a str, timeframe: i32, sessions: Vec<&'static str>, limit: i64) -> _ {
+  stocks::table
+    .filter(stocks::ticker.eq(ticker))
+    .filter(stocks::timeframe.eq(timeframe))
+    .filter(stocks::session.eq_any(sessions))
+    .order(stocks::timestamp.desc())
+    .limit(limit)
+}
+
+#[dsl::auto_type]
+fn recent_alerts(limit: i64) -> _ {
+  alerts::table.order(alerts::fired_at.desc()).limit(limit)
+}
+
+#[dsl::auto_type]
+fn recent_ticker_alerts<'a>(ticker: &'a str, limit: i64) -> _ {
+  alerts::table
+    .filter(alerts::ticker.eq(ticker))
+    .order(alerts::fired_at.desc())
+    .limit(limit)
+}
+
+#[dsl::auto_type]
+fn oldest_timestamps(timeframe: i32) -> _ {
+  let oldest: helper_types::min<stocks::timestamp> = dsl::min(stocks::timestamp);
+  stocks::table
+    .filter(stocks::timeframe.eq(timeframe))
+    .group_by(stocks::ticker)
+    .select((stocks::ticker, oldest))
+}
+
+/**
+ * Reads a ticker's saved candles of one timeframe between two timestamps, inclusive
+ *
+ * @return the candles oldest first
+ */
+pub fn load_stocks<C>(
+  conn: &mut C,
+  ticker: &str,
+  timeframe: u32,
+  timestamp_from: i64,
+  timestamp_to: i64,
+) -> QueryResult<Vec<Stock>>
+where
+  C: LoadConnection,
+  for<'a> Select<stocks_between<'a>, AsSelect<Stock, C::Backend>>: RunQueryDsl<C> + LoadQuery<'a, C, Stock>,
+{
+  stocks_between(ticker, timeframe as i32, timestamp_from, timestamp_to)
+    .select(Stock::as_select())
+    .load(conn)
+}
+
+/**
+ * Reads a ticker's newest saved candles of one timeframe
+ *
+ * @param sessions: candles from other sessions are skipped before the limit is applied
+ * @param limit: how many candles at most
+ * @return the candles oldest first
+ */
+pub fn load_latest_stocks<C>(
+  conn: &mut C,
+  ticker: &str,
+  timeframe: u32,
+  sessions: &[Session],
+  limit: i64,
+) -> QueryResult<Vec<Stock>>
+where
+  C: LoadConnection,
+  for<'a> Select<latest_stocks<'a>, AsSelect<Stock, C::Backend>>: RunQueryDsl<C> + LoadQuery<'a, C, Stock>,
+{
+  let sessions = sessions.iter().map(Session::as_str).collect();
+  let mut latest = latest_stocks(ticker, timeframe as i32, sessions, limit)
+    .select(Stock::as_select())
+    .load(conn)?;
+
+  latest.reverse();
+  Ok(latest)
+}
+
+/**
+ * Finds every ticker with saved candles of one timeframe
+ *
+ * @return each ticker with the timestamp of its oldest candle
+ */
+pub fn load_oldest_timestamps<C>(conn: &mut C, timeframe: u32) -> QueryResult<Vec<(String, i64)>>
+where
+  C: LoadConnection,
+  for<'a> oldest_timestamps: RunQueryDsl<C> + LoadQuery<'a, C, (String, Option<i64>)>,
+{
+  let oldest = oldest_timestamps(timeframe as i32).load::<(String, Option<i64>)>(conn)?;
+
+  Ok(oldest.into_iter().filter_map(|(ticker, timestamp)| Some((ticker, timestamp?))).collect())
+}
+
+/**
+ * Reads the most recently fired alerts, for one ticker or all of them
+ *
+ * @return at most limit alerts, newest first
+ */
+pub fn load_recent_alerts<C>(conn: &mut C, ticker: Option<&str>, limit: i64) -> QueryResult<Vec<Alert>>
+where
+  C: LoadConnection,
+  for<'a> Select<recent_alerts, AsSelect<Alert, C::Backend>>: RunQueryDsl<C> + LoadQuery<'a, C, Alert>,
+  for<'a> Select<recent_ticker_alerts<'a>, AsSelect<Alert, C::Backend>>: RunQueryDsl<C> + LoadQuery<'a, C, Alert>,
+{
+  match ticker {
+    Some(ticker) => recent_ticker_alerts(ticker, limit).select(Alert::as_select()).load(conn),
+    None => recent_alerts(limit).select(Alert::as_select()).load(conn),
+  }
+}
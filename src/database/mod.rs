@@ -1,2 +1,12 @@
-mod establish_connection;
-mod models;
\ No newline at end of file
+pub mod alert_repository;
+pub mod candle_repository;
+pub mod connection_pool;
+pub mod database_error;
+#[cfg(test)]
+pub mod in_memory_repository;
+pub mod load_stock_data;
+pub mod models;
+pub mod persist_stock_data;
+pub mod pg_repository;
+pub mod repositories;
+pub mod sqlite_repository;
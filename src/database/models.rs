@@ -1,8 +1,9 @@
 use diesel::prelude::*;
+use serde::Serialize;
 
-#[derive(Queryable, Selectable)]
+#[derive(Queryable, Selectable, Debug, Clone, Serialize)]
 #[diesel(table_name = crate::schema::stocks)]
-#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
 pub struct Stock{
   pub id: i32,
   pub ticker: String,
@@ -10,6 +11,152 @@ pub struct Stock{
   pub close: f64,
   pub high: f64,
   pub low: f64,
-  pub ema_9: f64,
+  pub ema_9: Option<f64>,
   pub timestamp: i64,
-}
\ No newline at end of file
+  pub timeframe: i32,
+  pub volume: i64,
+  pub ema_20: Option<f64>,
+  pub vwap: Option<f64>,
+  pub trade_count: Option<i32>,
+  pub session: String,
+  pub rsi_14: Option<f64>,
+  pub macd: Option<f64>,
+  pub macd_signal: Option<f64>,
+  pub macd_histogram: Option<f64>,
+  pub bollinger_middle: Option<f64>,
+  pub bollinger_upper: Option<f64>,
+  pub bollinger_lower: Option<f64>,
+  #[serde(skip)]
+  pub rsi_average_gain: Option<f64>,
+  #[serde(skip)]
+  pub rsi_average_loss: Option<f64>,
+  #[serde(skip)]
+  pub macd_fast_ema: Option<f64>,
+  #[serde(skip)]
+  pub macd_slow_ema: Option<f64>,
+}
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::stocks)]
+pub struct NewStock{
+  pub ticker: String,
+  pub open: f64,
+  pub close: f64,
+  pub high: f64,
+  pub low: f64,
+  pub ema_9: Option<f64>,
+  pub timestamp: i64,
+  pub timeframe: i32,
+  pub volume: i64,
+  pub ema_20: Option<f64>,
+  pub vwap: Option<f64>,
+  pub trade_count: Option<i32>,
+  pub session: String,
+  pub rsi_14: Option<f64>,
+  pub macd: Option<f64>,
+  pub macd_signal: Option<f64>,
+  pub macd_histogram: Option<f64>,
+  pub bollinger_middle: Option<f64>,
+  pub bollinger_upper: Option<f64>,
+  pub bollinger_lower: Option<f64>,
+  pub rsi_average_gain: Option<f64>,
+  pub rsi_average_loss: Option<f64>,
+  pub macd_fast_ema: Option<f64>,
+  pub macd_slow_ema: Option<f64>,
+}
+
+impl NewStock {
+  /**
+   * The row as it reads back once saved under id
+   */
+  pub fn with_id(self, id: i32) -> Stock {
+    Stock {
+      id,
+      ticker: self.ticker,
+      open: self.open,
+      close: self.close,
+      high: self.high,
+      low: self.low,
+      ema_9: self.ema_9,
+      timestamp: self.timestamp,
+      timeframe: self.timeframe,
+      volume: self.volume,
+      ema_20: self.ema_20,
+      vwap: self.vwap,
+      trade_count: self.trade_count,
+      session: self.session,
+      rsi_14: self.rsi_14,
+      macd: self.macd,
+      macd_signal: self.macd_signal,
+      macd_histogram: self.macd_histogram,
+      bollinger_middle: self.bollinger_middle,
+      bollinger_upper: self.bollinger_upper,
+      bollinger_lower: self.bollinger_lower,
+      rsi_average_gain: self.rsi_average_gain,
+      rsi_average_loss: self.rsi_average_loss,
+      macd_fast_ema: self.macd_fast_ema,
+      macd_slow_ema: self.macd_slow_ema,
+    }
+  }
+}
+
+#[derive(Queryable, Selectable, Debug, Clone, Serialize)]
+#[diesel(table_name = crate::schema::alerts)]
+#[diesel(check_for_backend(diesel::pg::Pg, diesel::sqlite::Sqlite))]
+pub struct Alert{
+  pub id: i32,
+  pub ticker: String,
+  pub alert_type: String,
+  pub candle_timestamp: i64,
+  pub fired_at: i64,
+  pub ema_9: Option<f64>,
+  pub ema_20: Option<f64>,
+  pub rsi_14: Option<f64>,
+  pub macd: Option<f64>,
+  pub macd_signal: Option<f64>,
+  pub macd_histogram: Option<f64>,
+  pub bollinger_middle: Option<f64>,
+  pub bollinger_upper: Option<f64>,
+  pub bollinger_lower: Option<f64>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::alerts)]
+pub struct NewAlert{
+  pub ticker: String,
+  pub alert_type: String,
+  pub candle_timestamp: i64,
+  pub fired_at: i64,
+  pub ema_9: Option<f64>,
+  pub ema_20: Option<f64>,
+  pub rsi_14: Option<f64>,
+  pub macd: Option<f64>,
+  pub macd_signal: Option<f64>,
+  pub macd_histogram: Option<f64>,
+  pub bollinger_middle: Option<f64>,
+  pub bollinger_upper: Option<f64>,
+  pub bollinger_lower: Option<f64>,
+}
+
+impl NewAlert {
+  /**
+   * The row as it reads back once saved under id
+   */
+  pub fn with_id(self, id: i32) -> Alert {
+    Alert {
+      id,
+      ticker: self.ticker,
+      alert_type: self.alert_type,
+      candle_timestamp: self.candle_timestamp,
+      fired_at: self.fired_at,
+      ema_9: self.ema_9,
+      ema_20: self.ema_20,
+      rsi_14: self.rsi_14,
+      macd: self.macd,
+      macd_signal: self.macd_signal,
+      macd_histogram: self.macd_histogram,
+      bollinger_middle: self.bollinger_middle,
+      bollinger_upper: self.bollinger_upper,
+      bollinger_lower: self.bollinger_lower,
+    }
+  }
+}
@@ -0,0 +1,128 @@
+use diesel::{
+  dsl::{self, Values},
+  pg::Pg,
+  prelude::*,
+  query_builder::QueryFragment,
+  query_dsl::methods::ExecuteDsl,
+  sqlite::Sqlite,
+  upsert::excluded,
+};
+
+use crate::{
+  database::models::{NewAlert, NewStock},
+  schema::{alerts, stocks},
+};
+
+// Postgres allows 65535 bind parameters per statement, a stocks row uses 24
+const STOCKS_PER_INSERT: usize = 2_500;
+
+/**
+ * Columns a saved candle is updated with when the same ticker, timeframe and timestamp is written again.
+ * Shared by the Postgres and SQLite upserts, so both overwrite the same columns.
+ */
+fn upserted_columns()
+-> impl AsChangeset<Target = stocks::table, Changeset: QueryFragment<Pg> + QueryFragment<Sqlite>> {
+  (
+    stocks::open.eq(excluded(stocks::open)),
+    stocks::close.eq(excluded(stocks::close)),
+    stocks::high.eq(excluded(stocks::high)),
+    stocks::low.eq(excluded(stocks::low)),
+    stocks::ema_9.eq(excluded(stocks::ema_9)),
+    stocks::volume.eq(excluded(stocks::volume)),
+    stocks::ema_20.eq(excluded(stocks::ema_20)),
+    stocks::vwap.eq(excluded(stocks::vwap)),
+    stocks::trade_count.eq(excluded(stocks::trade_count)),
+    stocks::session.eq(excluded(stocks::session)),
+    stocks::rsi_14.eq(excluded(stocks::rsi_14)),
+    stocks::macd.eq(excluded(stocks::macd)),
+    stocks::macd_signal.eq(excluded(stocks::macd_signal)),
+    stocks::macd_histogram.eq(excluded(stocks::macd_histogram)),
+    stocks::bollinger_middle.eq(excluded(stocks::bollinger_middle)),
+    stocks::bollinger_upper.eq(excluded(stocks::bollinger_upper)),
+    stocks::bollinger_lower.eq(excluded(stocks::bollinger_lower)),
+    stocks::rsi_average_gain.eq(excluded(stocks::rsi_average_gain)),
+    stocks::rsi_average_loss.eq(excluded(stocks::rsi_average_loss)),
+    stocks::macd_fast_ema.eq(excluded(stocks::macd_fast_ema)),
+    stocks::macd_slow_ema.eq(excluded(stocks::macd_slow_ema)),
+  )
+}
+
+/**
+ * Writes candles to the stocks table. A candle already saved for the same ticker, timeframe
+ * and timestamp is overwritten, so writing the same candles twice is harmless.
+ *
+ * @return the number of rows inserted or updated
+ */
+pub fn upsert_stocks(conn: &mut PgConnection, new_stocks: &[NewStock]) -> QueryResult<usize> {
+  conn.transaction(|conn| {
+    let mut written = 0;
+    for batch in new_stocks.chunks(STOCKS_PER_INSERT) {
+      written += diesel::insert_into(stocks::table)
+        .values(batch)
+        .on_conflict((stocks::ticker, stocks::timeframe, stocks::timestamp))
+        .do_update()
+        .set(upserted_columns())
+        .execute(conn)?;
+    }
+    Ok(written)
+  })
+}
+
+/**
+ * Same upsert on SQLite, one row per statement since diesel cannot batch an upsert there.
+ * A single transaction keeps it to one write to disk.
+ *
+ * @return the number of rows inserted or updated
+ */
+pub fn upsert_stocks_one_by_one(conn: &mut SqliteConnection, new_stocks: &[NewStock]) -> QueryResult<usize> {
+  conn.transaction(|conn| {
+    let mut written = 0;
+    for new_stock in new_stocks {
+      written += diesel::insert_into(stocks::table)
+        .values(new_stock)
+        .on_conflict((stocks::ticker, stocks::timeframe, stocks::timestamp))
+        .do_update()
+        .set(upserted_columns())
+        .execute(conn)?;
+    }
+    Ok(written)
+  })
+}
+
+/**
+ * Deletes a ticker's candles of one timeframe with a timestamp earlier than before
+ *
+ * @return the number of rows deleted
+ */
+pub fn delete_stocks_before<C>(conn: &mut C, ticker: &str, timeframe: u32, before: i64) -> QueryResult<usize>
+where
+  C: Connection,
+  for<'a> stocks_before<'a>: ExecuteDsl<C>,
+{
+  stocks_before(ticker, timeframe as i32, before).execute(conn)
+}
+
+#[dsl::auto_type]
+fn stocks_before<'a>(ticker: &'a str, timeframe: i32, before: i64) -> _ {
+  dsl::delete(
+    stocks::table
+      .filter(stocks::ticker.eq(ticker))
+      .filter(stocks::timeframe.eq(timeframe))
+      .filter(stocks::timestamp.lt(before)),
+  )
+}
+
+/**
+ * Writes fired alerts to the alerts table
+ *
+ * @return the number of rows inserted
+ */
+pub fn insert_alerts<'a, C>(conn: &mut C, new_alerts: &'a [NewAlert]) -> QueryResult<usize>
+where
+  C: Connection,
+  Values<dsl::insert_into<alerts::table>, &'a [NewAlert]>: ExecuteDsl<C>,
+{
+  diesel::insert_into(alerts::table)
+    .values(new_alerts)
+    .execute(conn)
+}
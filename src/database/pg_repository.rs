@@ -0,0 +1,84 @@
+use async_trait::async_trait;
+use diesel::sql_query;
+use diesel::prelude::*;
+
+use crate::{
+    database::{
+        alert_repository::AlertRepository,
+        candle_repository::CandleRepository,
+        connection_pool::{with_connection, PgPool},
+        database_error::DatabaseError,
+        load_stock_data::{load_latest_stocks, load_oldest_timestamps, load_recent_alerts, load_stocks},
+        models::{Alert, NewAlert, NewStock, Stock},
+        persist_stock_data::{delete_stocks_before, insert_alerts, upsert_stocks},
+    },
+    market_calendar::session::Session,
+};
+
+/**
+ * Candles and alerts in Postgres, every query runs on a pooled connection off the async runtime
+ */
+pub struct PgRepository {
+    pool: PgPool,
+}
+
+impl PgRepository {
+    pub fn new(pool: PgPool) -> Self {
+        PgRepository { pool }
+    }
+}
+
+#[async_trait]
+impl CandleRepository for PgRepository {
+    async fn save_candles(&self, candles: Vec<NewStock>) -> Result<usize, DatabaseError> {
+        with_connection(&self.pool, move |conn| upsert_stocks(conn, &candles)).await
+    }
+
+    async fn latest_candles(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        sessions: &[Session],
+        limit: usize,
+    ) -> Result<Vec<Stock>, DatabaseError> {
+        let ticker = ticker.to_string();
+        let sessions = sessions.to_vec();
+        with_connection(&self.pool, move |conn| load_latest_stocks(conn, &ticker, timeframe, &sessions, limit as i64)).await
+    }
+
+    async fn candles_between(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        timestamp_from: i64,
+        timestamp_to: i64,
+    ) -> Result<Vec<Stock>, DatabaseError> {
+        let ticker = ticker.to_string();
+        with_connection(&self.pool, move |conn| load_stocks(conn, &ticker, timeframe, timestamp_from, timestamp_to)).await
+    }
+
+    async fn oldest_candles(&self, timeframe: u32) -> Result<Vec<(String, i64)>, DatabaseError> {
+        with_connection(&self.pool, move |conn| load_oldest_timestamps(conn, timeframe)).await
+    }
+
+    async fn delete_candles_before(&self, ticker: &str, timeframe: u32, before: i64) -> Result<usize, DatabaseError> {
+        let ticker = ticker.to_string();
+        with_connection(&self.pool, move |conn| delete_stocks_before(conn, &ticker, timeframe, before)).await
+    }
+
+    async fn check_health(&self) -> Result<(), DatabaseError> {
+        with_connection(&self.pool, |conn| sql_query("SELECT 1").execute(conn).map(|_| ())).await
+    }
+}
+
+#[async_trait]
+impl AlertRepository for PgRepository {
+    async fn save_alerts(&self, alerts: Vec<NewAlert>) -> Result<usize, DatabaseError> {
+        with_connection(&self.pool, move |conn| insert_alerts(conn, &alerts)).await
+    }
+
+    async fn recent_alerts(&self, ticker: Option<&str>, limit: usize) -> Result<Vec<Alert>, DatabaseError> {
+        let ticker = ticker.map(str::to_string);
+        with_connection(&self.pool, move |conn| load_recent_alerts(conn, ticker.as_deref(), limit as i64)).await
+    }
+}
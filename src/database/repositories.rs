@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+#[cfg(test)]
+use crate::database::in_memory_repository::InMemoryRepository;
+
+use crate::database::{
+    alert_repository::AlertRepository,
+    candle_repository::CandleRepository,
+    connection_pool::{PgPool, SqlitePool},
+    pg_repository::PgRepository,
+    sqlite_repository::SqliteRepository,
+};
+
+/**
+ * Every store the monitor writes to, handed to the polling loop and the HTTP handlers together
+ */
+#[derive(Clone)]
+pub struct Repositories {
+    pub candles: Arc<dyn CandleRepository>,
+    pub alerts: Arc<dyn AlertRepository>,
+}
+
+impl Repositories {
+    pub fn postgres(pool: PgPool) -> Self {
+        let repository = Arc::new(PgRepository::new(pool));
+        Repositories {
+            candles: repository.clone(),
+            alerts: repository,
+        }
+    }
+
+    pub fn sqlite(pool: SqlitePool) -> Self {
+        let repository = Arc::new(SqliteRepository::new(pool));
+        Repositories {
+            candles: repository.clone(),
+            alerts: repository,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        let repository = Arc::new(InMemoryRepository::new());
+        Repositories {
+            candles: repository.clone(),
+            alerts: repository,
+        }
+    }
+}
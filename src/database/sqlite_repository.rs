@@ -0,0 +1,152 @@
+use async_trait::async_trait;
+use diesel::{prelude::*, sql_query};
+
+use crate::{
+    database::{
+        alert_repository::AlertRepository,
+        candle_repository::CandleRepository,
+        connection_pool::{with_connection, SqlitePool},
+        database_error::DatabaseError,
+        load_stock_data::{load_latest_stocks, load_oldest_timestamps, load_recent_alerts, load_stocks},
+        models::{Alert, NewAlert, NewStock, Stock},
+        persist_stock_data::{delete_stocks_before, insert_alerts, upsert_stocks_one_by_one},
+    },
+    market_calendar::session::Session,
+};
+
+/**
+ * Candles and alerts in a SQLite file, for running on one machine without Postgres.
+ * Runs the same queries as PgRepository so both backends behave the same.
+ */
+pub struct SqliteRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        SqliteRepository { pool }
+    }
+}
+
+#[async_trait]
+impl CandleRepository for SqliteRepository {
+    async fn save_candles(&self, candles: Vec<NewStock>) -> Result<usize, DatabaseError> {
+        with_connection(&self.pool, move |conn| upsert_stocks_one_by_one(conn, &candles)).await
+    }
+
+    async fn latest_candles(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        sessions: &[Session],
+        limit: usize,
+    ) -> Result<Vec<Stock>, DatabaseError> {
+        let ticker = ticker.to_string();
+        let sessions = sessions.to_vec();
+        with_connection(&self.pool, move |conn| load_latest_stocks(conn, &ticker, timeframe, &sessions, limit as i64)).await
+    }
+
+    async fn candles_between(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        timestamp_from: i64,
+        timestamp_to: i64,
+    ) -> Result<Vec<Stock>, DatabaseError> {
+        let ticker = ticker.to_string();
+        with_connection(&self.pool, move |conn| load_stocks(conn, &ticker, timeframe, timestamp_from, timestamp_to)).await
+    }
+
+    async fn oldest_candles(&self, timeframe: u32) -> Result<Vec<(String, i64)>, DatabaseError> {
+        with_connection(&self.pool, move |conn| load_oldest_timestamps(conn, timeframe)).await
+    }
+
+    async fn delete_candles_before(&self, ticker: &str, timeframe: u32, before: i64) -> Result<usize, DatabaseError> {
+        let ticker = ticker.to_string();
+        with_connection(&self.pool, move |conn| delete_stocks_before(conn, &ticker, timeframe, before)).await
+    }
+
+    async fn check_health(&self) -> Result<(), DatabaseError> {
+        with_connection(&self.pool, |conn| sql_query("SELECT 1").execute(conn).map(|_| ())).await
+    }
+}
+
+#[async_trait]
+impl AlertRepository for SqliteRepository {
+    async fn save_alerts(&self, alerts: Vec<NewAlert>) -> Result<usize, DatabaseError> {
+        with_connection(&self.pool, move |conn| insert_alerts(conn, &alerts)).await
+    }
+
+    async fn recent_alerts(&self, ticker: Option<&str>, limit: usize) -> Result<Vec<Alert>, DatabaseError> {
+        let ticker = ticker.map(str::to_string);
+        with_connection(&self.pool, move |conn| load_recent_alerts(conn, ticker.as_deref(), limit as i64)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use crate::{
+        database::connection_pool::open_sqlite,
+        test_fixtures::{fired_alert, saved_candle},
+    };
+
+    use super::*;
+
+    fn candle(timestamp: i64, close: f64) -> NewStock {
+        NewStock {
+            close,
+            high: 101.0,
+            low: 99.0,
+            vwap: Some(100.2),
+            trade_count: Some(12),
+            rsi_14: Some(55.0),
+            macd: Some(0.4),
+            macd_slow_ema: Some(99.6),
+            ..saved_candle(15, timestamp)
+        }
+    }
+
+    #[tokio::test]
+    async fn migrates_a_new_file_and_upserts_candles() {
+        let path = env::temp_dir().join(format!("stock_monitor_sqlite_{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let repository = SqliteRepository::new(open_sqlite(path.to_str().unwrap()));
+
+        repository.save_candles(vec![candle(1, 100.0), candle(2, 100.0), candle(3, 100.0)]).await.unwrap();
+        // Saving a candle again replaces it instead of failing on the unique index
+        repository.save_candles(vec![candle(3, 105.0)]).await.unwrap();
+        repository.save_candles(vec![NewStock { session: "after_hours".to_string(), ..candle(4, 106.0) }]).await.unwrap();
+
+        let latest = repository.latest_candles("QQQ", 15, &[Session::Regular], 2).await.unwrap();
+        assert_eq!(latest.iter().map(|stock| (stock.timestamp, stock.close)).collect::<Vec<_>>(), vec![(2, 100.0), (3, 105.0)]);
+        assert_eq!(latest[1].trade_count, Some(12));
+        assert_eq!((latest[1].rsi_14, latest[1].macd, latest[1].macd_signal), (Some(55.0), Some(0.4), None));
+        assert_eq!((latest[1].macd_slow_ema, latest[1].macd_fast_ema), (Some(99.6), None));
+        assert_eq!(repository.latest_candles("QQQ", 15, &Session::ALL, 2).await.unwrap().last().unwrap().timestamp, 4);
+        assert_eq!(repository.candles_between("QQQ", 15, 1, 3).await.unwrap().len(), 3);
+        assert!(repository.candles_between("QQQ", 5, 1, 3).await.unwrap().is_empty());
+        assert_eq!(repository.oldest_candles(15).await.unwrap(), vec![("QQQ".to_string(), 1)]);
+        assert_eq!(repository.delete_candles_before("QQQ", 15, 3).await.unwrap(), 2);
+        assert_eq!(repository.oldest_candles(15).await.unwrap(), vec![("QQQ".to_string(), 3)]);
+
+        repository
+            .save_alerts(vec![
+                fired_alert("QQQ", 1_000_000),
+                fired_alert("NVDA", 2_000_000),
+                NewAlert { rsi_14: Some(71.0), macd_histogram: Some(-0.2), ..fired_alert("QQQ", 3_000_000) },
+            ])
+            .await
+            .unwrap();
+        let recent = repository.recent_alerts(None, 10).await.unwrap();
+        assert_eq!(recent.iter().map(|alert| alert.fired_at).collect::<Vec<_>>(), vec![3_000_000, 2_000_000, 1_000_000]);
+        let qqq = repository.recent_alerts(Some("QQQ"), 1).await.unwrap();
+        assert_eq!(qqq.iter().map(|alert| (alert.ticker.as_str(), alert.fired_at)).collect::<Vec<_>>(), vec![("QQQ", 3_000_000)]);
+        assert_eq!((qqq[0].rsi_14, qqq[0].macd_histogram, qqq[0].ema_9), (Some(71.0), Some(-0.2), None));
+
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use serde::Serialize;
+
+use crate::indicators::{indicator::Indicator, sma::Sma};
+
+/**
+ * @middle: simple average of the window
+ * @upper: middle plus the band width in standard deviations
+ * @lower: middle minus the band width in standard deviations
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BollingerValue {
+    pub middle: f64,
+    pub upper: f64,
+    pub lower: f64,
+}
+
+/**
+ * Bollinger bands, e.g. (20, 2): the 20 close SMA with bands 2 population standard deviations either side
+ */
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    sma: Sma,
+    width: f64,
+}
+
+impl Bollinger {
+    pub fn new(period: usize, width: f64) -> Self {
+        Bollinger {
+            sma: Sma::new(period),
+            width,
+        }
+    }
+}
+
+impl Indicator for Bollinger {
+    type Output = BollingerValue;
+
+    fn update(&mut self, close: f64) -> Option<BollingerValue> {
+        self.sma.update(close);
+        return self.value();
+    }
+
+    fn value(&self) -> Option<BollingerValue> {
+        let middle = self.sma.value()?;
+        let window = self.sma.window();
+        let variance = window.iter().map(|close| (close - middle).powi(2)).sum::<f64>() / window.len() as f64;
+        let offset = self.width * variance.sqrt();
+
+        return Some(BollingerValue {
+            middle,
+            upper: middle + offset,
+            lower: middle - offset,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bands_are_standard_deviations_around_the_average() {
+        let mut bollinger = Bollinger::new(4, 2.0);
+
+        for close in [100.0, 2.0, 4.0] {
+            assert_eq!(bollinger.update(close), None);
+        }
+        assert!(bollinger.update(4.0).is_some());
+
+        // Once 100 has left the window, 2, 4, 4 and 6 average 4 with a standard deviation of sqrt(2)
+        let value = bollinger.update(6.0).unwrap();
+        assert_eq!(value.middle, 4.0);
+        assert!((value.upper - (4.0 + 2.0 * 2.0_f64.sqrt())).abs() < 1e-12);
+        assert!((value.lower - (4.0 - 2.0 * 2.0_f64.sqrt())).abs() < 1e-12);
+    }
+}
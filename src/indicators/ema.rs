@@ -0,0 +1,94 @@
+use crate::indicators::indicator::Indicator;
+
+/**
+ * Exponential moving average with the usual 2 / (period + 1) smoothing
+ *
+ * The first value is the simple average of the first period closes, and every close after that
+ * is blended into the previous value.
+ *
+ * @field seed_sum: sum of the closes seen before the first value
+ * @field seen: how many closes were added before the first value
+ */
+#[derive(Debug, Clone)]
+pub struct Ema {
+    period: usize,
+    multiplier: f64,
+    seed_sum: f64,
+    seen: usize,
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "an EMA needs a period of at least 1");
+        Ema {
+            period,
+            multiplier: 2.0 / (period as f64 + 1.0),
+            seed_sum: 0.0,
+            seen: 0,
+            value: None,
+        }
+    }
+
+    /**
+     * Continues from a value computed earlier, e.g. one saved to the database, instead of seeding again
+     */
+    pub fn resume(&mut self, value: f64) {
+        self.value = Some(value);
+    }
+}
+
+impl Indicator for Ema {
+    type Output = f64;
+
+    fn update(&mut self, close: f64) -> Option<f64> {
+        match self.value {
+            Some(previous) => self.value = Some(close * self.multiplier + previous * (1.0 - self.multiplier)),
+            None => {
+                self.seed_sum += close;
+                self.seen += 1;
+                if self.seen == self.period {
+                    self.value = Some(self.seed_sum / self.period as f64);
+                }
+            }
+        }
+        return self.value;
+    }
+
+    fn value(&self) -> Option<f64> {
+        return self.value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Closes from StockCharts' 10 day EMA example, which lists the EMA as 22.22, 22.21 and 22.24 once rounded
+    const CLOSES: [f64; 12] = [22.27, 22.19, 22.08, 22.17, 22.18, 22.13, 22.23, 22.43, 22.24, 22.29, 22.15, 22.39];
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("the EMA has a value");
+        assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn seeds_with_the_simple_average_then_smooths() {
+        let mut ema = Ema::new(10);
+
+        for close in &CLOSES[..9] {
+            assert_eq!(ema.update(*close), None);
+        }
+        assert_close(ema.update(CLOSES[9]), 22.221);
+        assert_close(ema.update(CLOSES[10]), 22.20809090909091);
+        assert_close(ema.update(CLOSES[11]), 22.2411652892562);
+    }
+
+    #[test]
+    fn resumes_from_a_saved_value() {
+        let mut ema = Ema::new(10);
+        ema.resume(22.221);
+
+        assert_close(ema.update(CLOSES[10]), 22.20809090909091);
+    }
+}
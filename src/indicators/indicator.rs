@@ -0,0 +1,21 @@
+/**
+ * A technical indicator updated one close at a time
+ *
+ * Each indicator keeps every bit of history it needs itself, so it does not depend on how many
+ * candles StockData still holds and keeps working after old candles are evicted.
+ */
+pub trait Indicator {
+    type Output;
+
+    /**
+     * Adds the next candle's close
+     *
+     * @return the indicator's value including that close, None until enough closes have been seen
+     */
+    fn update(&mut self, close: f64) -> Option<Self::Output>;
+
+    /**
+     * The value after the last update, None until enough closes have been seen
+     */
+    fn value(&self) -> Option<Self::Output>;
+}
@@ -0,0 +1,193 @@
+use serde::Serialize;
+
+use crate::indicators::{
+    bollinger::{Bollinger, BollingerValue},
+    ema::Ema,
+    indicator::Indicator,
+    macd::{Macd, MacdValue},
+    rsi::Rsi,
+};
+
+/**
+ * The indicators computed for every candle of a ticker, one value each, None while still warming up
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct IndicatorValues {
+    pub ema_9: Option<f64>,
+    pub ema_20: Option<f64>,
+    pub rsi_14: Option<f64>,
+    pub macd: Option<MacdValue>,
+    pub bollinger: Option<BollingerValue>,
+}
+
+/**
+ * Running state behind IndicatorValues that cannot be worked out from the values alone,
+ * saved with each candle so RSI and MACD continue exactly where they were after a restart
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IndicatorState {
+    pub rsi_average_gain: Option<f64>,
+    pub rsi_average_loss: Option<f64>,
+    pub macd_fast_ema: Option<f64>,
+    pub macd_slow_ema: Option<f64>,
+}
+
+/**
+ * The state of every indicator kept for a ticker, fed each candle's close as it is added
+ */
+#[derive(Debug, Clone)]
+pub struct IndicatorSet {
+    ema_9: Ema,
+    ema_20: Ema,
+    rsi_14: Rsi,
+    macd: Macd,
+    bollinger: Bollinger,
+}
+
+impl IndicatorSet {
+    // Closes until every indicator has a value, MACD's 26 + 9 - 1. Bollinger bands need the last 20 closes themselves.
+    pub const WARM_UP_CANDLES: usize = 34;
+
+    pub fn new() -> Self {
+        IndicatorSet {
+            ema_9: Ema::new(9),
+            ema_20: Ema::new(20),
+            rsi_14: Rsi::new(14),
+            macd: Macd::new(12, 26, 9),
+            bollinger: Bollinger::new(20, 2.0),
+        }
+    }
+
+    /**
+     * Adds the next candle's close to every indicator
+     */
+    pub fn update(&mut self, close: f64) -> IndicatorValues {
+        return IndicatorValues {
+            ema_9: self.ema_9.update(close),
+            ema_20: self.ema_20.update(close),
+            rsi_14: self.rsi_14.update(close),
+            macd: self.macd.update(close),
+            bollinger: self.bollinger.update(close),
+        };
+    }
+
+    /**
+     * Adds a candle read back from the database. The EMAs, RSI and MACD continue from the values and
+     * state it was saved with, so a restart does not seed them again. Anything saved while still
+     * warming up is rebuilt from the close instead, as are the Bollinger bands, which only need the
+     * last 20 closes.
+     */
+    pub fn restore(&mut self, close: f64, saved: &IndicatorValues, state: &IndicatorState) {
+        match saved.ema_9 {
+            Some(ema_9) => self.ema_9.resume(ema_9),
+            None => {
+                self.ema_9.update(close);
+            }
+        }
+        match saved.ema_20 {
+            Some(ema_20) => self.ema_20.resume(ema_20),
+            None => {
+                self.ema_20.update(close);
+            }
+        }
+        match (state.rsi_average_gain, state.rsi_average_loss) {
+            (Some(average_gain), Some(average_loss)) => self.rsi_14.resume(close, average_gain, average_loss),
+            _ => {
+                self.rsi_14.update(close);
+            }
+        }
+        match (state.macd_fast_ema, state.macd_slow_ema, saved.macd) {
+            (Some(fast), Some(slow), Some(macd)) => self.macd.resume(fast, slow, macd.signal),
+            _ => {
+                self.macd.update(close);
+            }
+        }
+        self.bollinger.update(close);
+    }
+
+    /**
+     * The state to save next to values(), for restore to continue from
+     */
+    pub fn state(&self) -> IndicatorState {
+        let (macd_fast_ema, macd_slow_ema) = self.macd.emas();
+        let averages = self.rsi_14.averages();
+        return IndicatorState {
+            rsi_average_gain: averages.map(|(average_gain, _)| average_gain),
+            rsi_average_loss: averages.map(|(_, average_loss)| average_loss),
+            macd_fast_ema,
+            macd_slow_ema,
+        };
+    }
+
+    /**
+     * What every indicator holds right now, tests compare restored sets with running ones through it
+     */
+    #[cfg(test)]
+    pub fn values(&self) -> IndicatorValues {
+        return IndicatorValues {
+            ema_9: self.ema_9.value(),
+            ema_20: self.ema_20.value(),
+            rsi_14: self.rsi_14.value(),
+            macd: self.macd.value(),
+            bollinger: self.bollinger.value(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_fixtures::{assert_indicators_close, wavy_closes};
+
+    use super::*;
+
+    #[test]
+    fn every_indicator_has_a_value_after_the_warm_up() {
+        let mut indicators = IndicatorSet::new();
+        let closes = wavy_closes(IndicatorSet::WARM_UP_CANDLES);
+
+        for close in &closes[..closes.len() - 1] {
+            indicators.update(*close);
+        }
+        assert!(indicators.values().macd.is_none());
+
+        let values = indicators.update(closes[closes.len() - 1]);
+        assert!(values.ema_9.is_some() && values.ema_20.is_some() && values.rsi_14.is_some());
+        assert!(values.macd.is_some() && values.bollinger.is_some());
+        assert_eq!(values, indicators.values());
+    }
+
+    #[test]
+    fn restoring_saved_values_and_state_continues_where_they_were() {
+        let closes = wavy_closes(100);
+        let mut running = IndicatorSet::new();
+        let saved: Vec<(f64, IndicatorValues, IndicatorState)> =
+            closes[..80].iter().map(|close| (*close, running.update(*close), running.state())).collect();
+
+        // Only the last 20 candles are read back, enough for the Bollinger window
+        let mut restored = IndicatorSet::new();
+        for (close, values, state) in &saved[60..] {
+            restored.restore(*close, values, state);
+        }
+        assert_indicators_close(&restored.values(), &running.values());
+
+        for close in &closes[80..] {
+            assert_indicators_close(&restored.update(*close), &running.update(*close));
+        }
+    }
+
+    #[test]
+    fn rows_saved_without_values_are_rebuilt_from_the_closes() {
+        let closes = wavy_closes(40);
+        let mut running = IndicatorSet::new();
+        for close in &closes {
+            running.update(*close);
+        }
+
+        // Rows saved while warming up, or before a column existed, hold nothing to continue from
+        let mut restored = IndicatorSet::new();
+        for close in &closes {
+            restored.restore(*close, &IndicatorValues::default(), &IndicatorState::default());
+        }
+        assert_indicators_close(&restored.values(), &running.values());
+    }
+}
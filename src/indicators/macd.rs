@@ -0,0 +1,126 @@
+use serde::Serialize;
+
+use crate::indicators::{ema::Ema, indicator::Indicator};
+
+/**
+ * @macd: fast EMA minus slow EMA
+ * @signal: EMA of the MACD line
+ * @histogram: MACD line minus the signal line
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MacdValue {
+    pub macd: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+/**
+ * Moving average convergence divergence, e.g. MACD(12, 26, 9)
+ *
+ * The MACD line starts once the slow EMA is seeded, and the signal line is an EMA of it, so the
+ * first value needs slow_period + signal_period - 1 closes.
+ */
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+    value: Option<MacdValue>,
+}
+
+impl Macd {
+    pub fn new(fast_period: usize, slow_period: usize, signal_period: usize) -> Self {
+        assert!(fast_period < slow_period, "the fast period of a MACD must be shorter than the slow one");
+        Macd {
+            fast: Ema::new(fast_period),
+            slow: Ema::new(slow_period),
+            signal: Ema::new(signal_period),
+            value: None,
+        }
+    }
+
+    /**
+     * Continues from values computed earlier, e.g. ones saved to the database, instead of seeding again
+     */
+    pub fn resume(&mut self, fast: f64, slow: f64, signal: f64) {
+        self.fast.resume(fast);
+        self.slow.resume(slow);
+        self.signal.resume(signal);
+        let macd = fast - slow;
+        self.value = Some(MacdValue {
+            macd,
+            signal,
+            histogram: macd - signal,
+        });
+    }
+
+    /**
+     * @return the fast and the slow EMA, each None until seeded
+     */
+    pub fn emas(&self) -> (Option<f64>, Option<f64>) {
+        return (self.fast.value(), self.slow.value());
+    }
+}
+
+impl Indicator for Macd {
+    type Output = MacdValue;
+
+    fn update(&mut self, close: f64) -> Option<MacdValue> {
+        let fast = self.fast.update(close);
+        let slow = self.slow.update(close);
+
+        if let (Some(fast), Some(slow)) = (fast, slow) {
+            let macd = fast - slow;
+            self.value = self.signal.update(macd).map(|signal| MacdValue {
+                macd,
+                signal,
+                histogram: macd - signal,
+            });
+        }
+
+        return self.value;
+    }
+
+    fn value(&self) -> Option<MacdValue> {
+        return self.value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signal_is_an_ema_of_the_macd_line() {
+        let mut macd = Macd::new(2, 3, 2);
+
+        // On the third close the fast EMA is 2.5 and the slow one seeds at 2, the signal needs one more MACD value
+        assert_eq!(macd.update(1.0), None);
+        assert_eq!(macd.update(2.0), None);
+        assert_eq!(macd.update(3.0), None);
+
+        // Fast 4 * 2/3 + 2.5 / 3 = 3.5 and slow 4 / 2 + 2 / 2 = 3, then a close of 23 moves the line to 16.5 - 13 = 3.5
+        let value = macd.update(4.0).unwrap();
+        assert!((value.macd - 0.5).abs() < 1e-12 && (value.signal - 0.5).abs() < 1e-12);
+
+        let value = macd.update(23.0).unwrap();
+        let signal = 3.5 * 2.0 / 3.0 + 0.5 / 3.0;
+        assert!((value.macd - 3.5).abs() < 1e-12);
+        assert!((value.signal - signal).abs() < 1e-12);
+        assert!((value.histogram - (3.5 - signal)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn resumes_from_saved_emas() {
+        let mut macd = Macd::new(2, 3, 2);
+        for close in [1.0, 2.0, 3.0, 4.0] {
+            macd.update(close);
+        }
+        let (fast, slow) = macd.emas();
+
+        let mut resumed = Macd::new(2, 3, 2);
+        resumed.resume(fast.unwrap(), slow.unwrap(), macd.value().unwrap().signal);
+        assert_eq!(resumed.value(), macd.value());
+        assert_eq!(resumed.update(23.0), macd.update(23.0));
+    }
+}
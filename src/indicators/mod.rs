@@ -0,0 +1,7 @@
+pub mod bollinger;
+pub mod ema;
+pub mod indicator;
+pub mod indicator_set;
+pub mod macd;
+pub mod rsi;
+pub mod sma;
@@ -0,0 +1,144 @@
+use crate::indicators::indicator::Indicator;
+
+/**
+ * Relative strength index with Wilder's smoothing, between 0 and 100
+ *
+ * The first value needs period changes, so period + 1 closes. Average gain and loss start as the
+ * simple average of those changes and after that each new change is weighted 1 / period.
+ *
+ * @field previous_close: the last close, changes are measured from it
+ * @field seen: how many changes were added before the first value
+ */
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    period: usize,
+    previous_close: Option<f64>,
+    seen: usize,
+    average_gain: f64,
+    average_loss: f64,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "an RSI needs a period of at least 1");
+        Rsi {
+            period,
+            previous_close: None,
+            seen: 0,
+            average_gain: 0.0,
+            average_loss: 0.0,
+        }
+    }
+
+    /**
+     * Continues from averages computed earlier, e.g. ones saved to the database, instead of seeding again
+     *
+     * @param previous_close: the close the averages include last, the next change is measured from it
+     */
+    pub fn resume(&mut self, previous_close: f64, average_gain: f64, average_loss: f64) {
+        self.previous_close = Some(previous_close);
+        self.seen = self.period;
+        self.average_gain = average_gain;
+        self.average_loss = average_loss;
+    }
+
+    /**
+     * @return average gain and average loss, None until seeded
+     */
+    pub fn averages(&self) -> Option<(f64, f64)> {
+        if self.seen < self.period {
+            return None;
+        }
+        return Some((self.average_gain, self.average_loss));
+    }
+}
+
+impl Indicator for Rsi {
+    type Output = f64;
+
+    fn update(&mut self, close: f64) -> Option<f64> {
+        // The first close only gives the next one something to change from
+        let previous_close = self.previous_close.replace(close)?;
+
+        let change = close - previous_close;
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+        let period = self.period as f64;
+
+        if self.seen < self.period {
+            // Sums until the seed, which is their simple average
+            self.seen += 1;
+            self.average_gain += gain;
+            self.average_loss += loss;
+            if self.seen == self.period {
+                self.average_gain /= period;
+                self.average_loss /= period;
+            }
+        } else {
+            self.average_gain = (self.average_gain * (period - 1.0) + gain) / period;
+            self.average_loss = (self.average_loss * (period - 1.0) + loss) / period;
+        }
+
+        return self.value();
+    }
+
+    fn value(&self) -> Option<f64> {
+        if self.seen < self.period {
+            return None;
+        }
+        if self.average_loss == 0.0 {
+            // Only gains is as strong as it gets, no movement at all sits in the middle
+            return Some(if self.average_gain == 0.0 { 50.0 } else { 100.0 });
+        }
+        return Some(100.0 - 100.0 / (1.0 + self.average_gain / self.average_loss));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Closes from StockCharts' 14 day RSI example, with the values Wilder's formulas give for them
+    const CLOSES: [f64; 17] = [
+        44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03, 45.61, 46.28, 46.28, 46.00, 46.03,
+    ];
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("the RSI has a value");
+        assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn seeds_with_simple_averages_then_smooths() {
+        let mut rsi = Rsi::new(14);
+
+        for close in &CLOSES[..14] {
+            assert_eq!(rsi.update(*close), None);
+        }
+        assert_close(rsi.update(CLOSES[14]), 70.46413502109705);
+        assert_close(rsi.update(CLOSES[15]), 66.24961855355505);
+        assert_close(rsi.update(CLOSES[16]), 66.48094183471265);
+    }
+
+    #[test]
+    fn resumes_from_saved_averages() {
+        let mut rsi = Rsi::new(14);
+        for close in &CLOSES[..15] {
+            rsi.update(*close);
+        }
+        let (average_gain, average_loss) = rsi.averages().unwrap();
+
+        let mut resumed = Rsi::new(14);
+        resumed.resume(CLOSES[14], average_gain, average_loss);
+        assert_eq!(resumed.value(), rsi.value());
+        assert_close(resumed.update(CLOSES[15]), 66.24961855355505);
+    }
+
+    #[test]
+    fn only_gains_is_100() {
+        let mut rsi = Rsi::new(2);
+
+        rsi.update(1.0);
+        rsi.update(2.0);
+        assert_eq!(rsi.update(3.0), Some(100.0));
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::VecDeque;
+
+use crate::indicators::indicator::Indicator;
+
+/**
+ * Simple moving average of the last period closes
+ *
+ * @field window: the last period closes, oldest first
+ * @field sum: running sum of window
+ */
+#[derive(Debug, Clone)]
+pub struct Sma {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "an SMA needs a period of at least 1");
+        Sma {
+            period,
+            window: VecDeque::with_capacity(period + 1),
+            sum: 0.0,
+        }
+    }
+
+    /**
+     * The closes the average is taken over, oldest first
+     */
+    pub fn window(&self) -> &VecDeque<f64> {
+        return &self.window;
+    }
+}
+
+impl Indicator for Sma {
+    type Output = f64;
+
+    fn update(&mut self, close: f64) -> Option<f64> {
+        self.window.push_back(close);
+        self.sum += close;
+        if self.window.len() > self.period {
+            self.sum -= self.window.pop_front().unwrap();
+        }
+        return self.value();
+    }
+
+    fn value(&self) -> Option<f64> {
+        if self.window.len() < self.period {
+            return None;
+        }
+        return Some(self.sum / self.period as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_only_the_last_period_closes() {
+        let mut sma = Sma::new(3);
+
+        assert_eq!(sma.update(1.0), None);
+        assert_eq!(sma.update(2.0), None);
+        assert_eq!(sma.update(3.0), Some(2.0));
+        assert_eq!(sma.update(10.0), Some(5.0));
+        assert_eq!(sma.update(20.0), Some(11.0));
+        assert_eq!(sma.value(), Some(11.0));
+    }
+}
@@ -1,13 +1,50 @@
+// Explicit returns are the house style
+#![allow(clippy::needless_return)]
+
 mod aws_ses;
+mod backfill;
+mod backtest;
+mod cli;
+mod clock;
+mod config;
 mod polygon_api;
+mod replay;
+mod retention;
+mod routes;
 mod data_polling;
+mod market_calendar;
+mod market_data;
+mod notifier;
 mod database;
+mod indicators;
 mod schema;
+mod shutdown;
+#[cfg(test)]
+mod test_fixtures;
+mod twelve_data_api;
 
-use std::{collections::HashMap, io::Error};
-use axum::{ routing::{ get }, Router };
-use data_polling::setup_stock_data::setup_stock_data;
+use std::{collections::HashMap, io::Error, sync::Arc, time::Duration};
+use axum::{ routing::{ get, post }, Router };
+use backfill::run_backfill::run_backfill;
+use backtest::run_backtest::run_backtest;
+use clap::Parser;
+use cli::{Cli, Command};
+use clock::select_clock::select_clock;
+use config::load_config::{config_path, load_config};
+use data_polling::{constants::IS_DB_ENABLED, flush_stock_data::flush_on_shutdown, polling_loop::PollingContext, setup_stock_data::setup_stock_data};
+use database::connection_pool::connect_database;
+use dotenv::dotenv;
+use market_data::select_provider::select_provider;
+use notifier::email_notifier::EmailNotifier;
 use polygon_api::stock::StockData;
+use replay::run_replay::{run_replay, write_report};
+use retention::run_retention::run_retention;
+use routes::{
+    app_state::AppState, health::health, recent_alerts::recent_alerts, reload_watchlist::reload_watchlist,
+    ticker_candles::ticker_candles,
+};
+use shutdown::shutdown_signal::wait_for_shutdown_signal;
+use tokio::{sync::watch, time::{sleep, timeout}};
 use std::env;
 
 
@@ -17,7 +54,84 @@ async fn test() -> &'static str {
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let app = Router::new().route("/", get(test));
+    dotenv().ok();
+    let cli = Cli::parse();
+
+    let config_path = config_path();
+    let settings = match load_config(&config_path) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(Command::Replay(args)) = &cli.command {
+        let replayed = run_replay(&settings, args).await.and_then(|report| write_report(&report, args.report.as_deref()));
+        if let Err(e) = replayed {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Backtest(args)) = &cli.command {
+        let backtested = run_backtest(&settings, args).await.and_then(|report| write_report(&report, args.report.as_deref()));
+        if let Err(e) = backtested {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Backfill(args)) = &cli.command {
+        if let Err(e) = run_backfill(&settings, args).await {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let tickers = settings.tickers;
+
+    let provider = match select_provider(&settings.provider) {
+        Ok(provider) => provider,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("Fetching market data from {}", provider.name());
+    let clock = select_clock(&settings.clock);
+
+    // Alerts do not depend on the database, so a missing or unsupported DATABASE_URL runs without one rather than refusing to start.
+    // A database that is only down is kept, it is retried on every write
+    let database = if IS_DB_ENABLED {
+        match connect_database().await {
+            Ok(repositories) => Some(repositories),
+            Err(e) => {
+                eprintln!("{}, running without saving candles or alerts", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let (watchlist_tx, watchlist_rx) = watch::channel(tickers.clone());
+    let app_state = AppState {
+        config_path: Arc::new(config_path),
+        watchlist_tx: Arc::new(watchlist_tx),
+        database: database.clone(),
+    };
+
+    let app = Router::new()
+        .route("/", get(test))
+        .route("/health", get(health))
+        .route("/watchlist/reload", post(reload_watchlist))
+        .route("/tickers/{ticker}/candles", get(ticker_candles))
+        .route("/alerts", get(recent_alerts))
+        .with_state(app_state);
     let port = env
         ::var("PORT")
         .unwrap_or_else(|_| "5000".to_string())
@@ -31,20 +145,61 @@ async fn main() -> Result<(), Error> {
     };
 
     let listener = tokio::net::TcpListener::bind(address).await.unwrap();
-    let _ = tokio::spawn(async {
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    if let Some(repositories) = &database {
+        tokio::spawn(run_retention(
+            settings.retention.clone(),
+            repositories.candles.clone(),
+            clock.clone(),
+            shutdown_rx.clone(),
+        ));
+    }
+    let polling_task = tokio::spawn(async move {
         
-        let tickers = vec!["QQQ", "TSLA", "NVDA"]; // TODO move somewhere else
-        let mut initial_stock_data: HashMap<String, StockData> = setup_stock_data(&tickers);
+        let mut initial_stock_data: HashMap<String, StockData> = setup_stock_data(&tickers, database.as_ref()).await;
 
 
-        data_polling::polling_loop::monitor_stock_data(&mut initial_stock_data).await;
-        // let email_res = send_email().await;
-        // if let Err(e) = email_res {
-        //     eprintln!("Failed to send email {}", e);
-        // }
+        let context = PollingContext {
+            provider,
+            polling_config: settings.polling,
+            clock,
+            notifier: Arc::new(EmailNotifier),
+            database: database.clone(),
+        };
+        let mut pending_alerts = data_polling::polling_loop::monitor_stock_data(
+            &mut initial_stock_data,
+            watchlist_rx,
+            shutdown_rx,
+            context,
+        ).await;
+        flush_on_shutdown(&mut initial_stock_data, &mut pending_alerts, database.as_ref()).await;
     });
 
-    axum::serve(listener, app).await.unwrap();
+    // On a shutdown signal: stop polling and save what is in memory first, then let axum drain connections.
+    // If all of that takes longer than the deadline the process exits anyway.
+    let shutdown_deadline = Duration::from_secs(settings.shutdown.deadline_secs);
+    let graceful_shutdown = async move {
+        wait_for_shutdown_signal().await;
+        println!("Shutting down, deadline {}s", shutdown_deadline.as_secs());
+
+        tokio::spawn(async move {
+            sleep(shutdown_deadline).await;
+            eprintln!("Shutdown deadline passed, exiting without finishing");
+            std::process::exit(1);
+        });
+
+        let _ = shutdown_tx.send(true);
+        match timeout(shutdown_deadline, polling_task).await {
+            Ok(Ok(())) => println!("Polling loop stopped and flushed"),
+            Ok(Err(e)) => eprintln!("Polling loop ended with an error: {}", e),
+            Err(_) => eprintln!("Polling loop did not finish flushing before the deadline"),
+        }
+    };
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(graceful_shutdown)
+        .await
+        .unwrap();
   
     Ok(())
-}
\ No newline at end of file
+}
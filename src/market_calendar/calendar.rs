@@ -0,0 +1,436 @@
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc, Weekday};
+use chrono_tz::{America::New_York, Tz};
+
+use crate::{
+    market_calendar::{
+        holidays::{NYSE_EARLY_CLOSES, NYSE_HOLIDAYS},
+        session::Session,
+    },
+    market_data::asset_class::AssetClass,
+};
+
+// How far back previous_session looks, long enough to cover any run of weekends and holidays
+const MAX_DAYS_WITHOUT_SESSION: i64 = 10;
+
+/**
+ * Trading days and hours of a market
+ *
+ * Everything is evaluated in the market's own time zone, so the result does not depend on
+ * the host's time zone and daylight saving changes are handled by chrono-tz.
+ */
+pub struct MarketCalendar {
+    time_zone: Tz,
+    hours: TradingHours,
+}
+
+/**
+ * Exchange: a daily session with extended hours, holidays and early closes, e.g. NYSE
+ * AlwaysOpen: trades around the clock every day, e.g. crypto
+ * Weekly: trades continuously from one weekday and time to another, e.g. FX from Sunday to Friday
+ */
+enum TradingHours {
+    Exchange(ExchangeHours),
+    AlwaysOpen,
+    Weekly {
+        open_day: Weekday,
+        close_day: Weekday,
+        open_close_time: NaiveTime,
+    },
+}
+
+struct ExchangeHours {
+    pre_market_open: NaiveTime,
+    regular_open: NaiveTime,
+    regular_close: NaiveTime,
+    after_hours_close: NaiveTime,
+    early_close: NaiveTime,
+    early_after_hours_close: NaiveTime,
+    holidays: &'static [(i32, u32, u32)],
+    early_closes: &'static [(i32, u32, u32)],
+}
+
+/**
+ * Trading hours for one day
+ *
+ * @date: the trading day in the exchange's time zone
+ * @pre_market_open: when extended trading starts
+ * @open: when the regular session opens
+ * @close: when the regular session closes, earlier on half days
+ * @after_hours_close: when extended trading ends, earlier on half days
+ * @is_early_close: whether this is a half day
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradingSession {
+    pub date: NaiveDate,
+    pub pre_market_open: DateTime<Utc>,
+    pub open: DateTime<Utc>,
+    pub close: DateTime<Utc>,
+    pub after_hours_close: DateTime<Utc>,
+    pub is_early_close: bool,
+}
+
+impl TradingSession {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.open <= at && at < self.close
+    }
+
+    /**
+     * Whether a candle closing at candle_close covers trading time in this session,
+     * the candle closing exactly at the session close included
+     */
+    pub fn contains_candle_close(&self, candle_close: DateTime<Utc>) -> bool {
+        self.open < candle_close && candle_close <= self.close
+    }
+
+    /**
+     * Which part of the day the instant falls in, None outside extended hours
+     */
+    pub fn session_at(&self, at: DateTime<Utc>) -> Option<Session> {
+        if at < self.pre_market_open || at >= self.after_hours_close {
+            return None;
+        }
+        if at < self.open {
+            return Some(Session::PreMarket);
+        }
+        if at < self.close {
+            return Some(Session::Regular);
+        }
+        Some(Session::AfterHours)
+    }
+
+    pub fn minutes_since_open(&self, at: DateTime<Utc>) -> i64 {
+        (at - self.open).num_minutes()
+    }
+
+    pub fn minutes_until_close(&self, at: DateTime<Utc>) -> i64 {
+        (self.close - at).num_minutes()
+    }
+}
+
+impl MarketCalendar {
+    /**
+     * NYSE and Nasdaq: 9:30 to 16:00 New York time, 13:00 on early close days.
+     * Extended trading runs from 4:00 to 20:00, or 17:00 on early close days.
+     */
+    pub fn nyse() -> Self {
+        MarketCalendar {
+            time_zone: New_York,
+            hours: TradingHours::Exchange(ExchangeHours {
+                pre_market_open: NaiveTime::from_hms_opt(4, 0, 0).unwrap(),
+                regular_open: NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+                regular_close: NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+                after_hours_close: NaiveTime::from_hms_opt(20, 0, 0).unwrap(),
+                early_close: NaiveTime::from_hms_opt(13, 0, 0).unwrap(),
+                early_after_hours_close: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+                holidays: NYSE_HOLIDAYS,
+                early_closes: NYSE_EARLY_CLOSES,
+            }),
+        }
+    }
+
+    /**
+     * Crypto: every minute of every day, dates are UTC
+     */
+    pub fn always_open() -> Self {
+        MarketCalendar {
+            time_zone: chrono_tz::UTC,
+            hours: TradingHours::AlwaysOpen,
+        }
+    }
+
+    /**
+     * FX: Sunday 17:00 to Friday 17:00 New York time
+     */
+    pub fn forex() -> Self {
+        MarketCalendar {
+            time_zone: New_York,
+            hours: TradingHours::Weekly {
+                open_day: Weekday::Sun,
+                close_day: Weekday::Fri,
+                open_close_time: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            },
+        }
+    }
+
+    pub fn for_asset_class(asset_class: AssetClass) -> Self {
+        match asset_class {
+            AssetClass::Equity => Self::nyse(),
+            AssetClass::Crypto => Self::always_open(),
+            AssetClass::Forex => Self::forex(),
+        }
+    }
+
+    pub fn time_zone(&self) -> Tz {
+        self.time_zone
+    }
+
+    /**
+     * The calendar date in the market's time zone at the given instant
+     */
+    pub fn local_date(&self, at: DateTime<Utc>) -> NaiveDate {
+        at.with_timezone(&self.time_zone).date_naive()
+    }
+
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        match &self.hours {
+            TradingHours::Exchange(exchange) => contains_date(exchange.holidays, date),
+            _ => false,
+        }
+    }
+
+    pub fn is_early_close(&self, date: NaiveDate) -> bool {
+        match &self.hours {
+            TradingHours::Exchange(exchange) => contains_date(exchange.early_closes, date),
+            _ => false,
+        }
+    }
+
+    /**
+     * Whether the market trades at any point on the date
+     */
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        match &self.hours {
+            TradingHours::Exchange(_) => !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.is_holiday(date),
+            TradingHours::AlwaysOpen => true,
+            TradingHours::Weekly { open_day, close_day, .. } => {
+                is_weekday_between(date.weekday(), *open_day, *close_day)
+            }
+        }
+    }
+
+    /**
+     * The daily open and close of an exchange
+     *
+     * @return None on weekends and holidays, and always for markets without a daily open and close
+     */
+    pub fn session_on(&self, date: NaiveDate) -> Option<TradingSession> {
+        let exchange = match &self.hours {
+            TradingHours::Exchange(exchange) => exchange,
+            _ => return None,
+        };
+
+        if !self.is_trading_day(date) {
+            return None;
+        }
+
+        let is_early_close = self.is_early_close(date);
+        let (close, after_hours_close) = if is_early_close {
+            (exchange.early_close, exchange.early_after_hours_close)
+        } else {
+            (exchange.regular_close, exchange.after_hours_close)
+        };
+
+        Some(TradingSession {
+            date,
+            pre_market_open: self.to_utc(date, exchange.pre_market_open),
+            open: self.to_utc(date, exchange.regular_open),
+            close: self.to_utc(date, close),
+            after_hours_close: self.to_utc(date, after_hours_close),
+            is_early_close,
+        })
+    }
+
+    /**
+     * Which session a candle starting at the given instant belongs to.
+     * Markets that trade around the clock only have a regular session.
+     *
+     * @return None while the market is closed
+     */
+    pub fn session_at(&self, at: DateTime<Utc>) -> Option<Session> {
+        match &self.hours {
+            TradingHours::Exchange(_) => self.session_on(self.local_date(at))?.session_at(at),
+            TradingHours::AlwaysOpen => Some(Session::Regular),
+            TradingHours::Weekly { open_day, close_day, open_close_time } => {
+                let local = at.with_timezone(&self.time_zone);
+                let weekday = local.weekday();
+                let is_open = is_weekday_between(weekday, *open_day, *close_day)
+                    && !(weekday == *open_day && local.time() < *open_close_time)
+                    && !(weekday == *close_day && local.time() >= *open_close_time);
+
+                is_open.then_some(Session::Regular)
+            }
+        }
+    }
+
+    /**
+     * Which session a candle closing at candle_close belongs to, judged by its last instant
+     */
+    pub fn candle_session(&self, candle_close: DateTime<Utc>) -> Option<Session> {
+        self.session_at(candle_close - TimeDelta::milliseconds(1))
+    }
+
+    // The polling loop judges candles with candle_session, instants are only checked by tests so far
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn is_open(&self, at: DateTime<Utc>) -> bool {
+        self.session_at(at) == Some(Session::Regular)
+    }
+
+    /**
+     * The exchange session a candle closing at candle_close belongs to
+     *
+     * @return None when the candle falls entirely outside regular trading hours,
+     * and always for markets without a daily open and close
+     */
+    pub fn session_for_candle_close(&self, candle_close: DateTime<Utc>) -> Option<TradingSession> {
+        self.session_on(self.local_date(candle_close))
+            .filter(|session| session.contains_candle_close(candle_close))
+    }
+
+    /**
+     * The most recent exchange session that opened before the given instant, possibly still running
+     */
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn previous_session(&self, before: DateTime<Utc>) -> Option<TradingSession> {
+        let mut date = self.local_date(before);
+
+        for _ in 0..MAX_DAYS_WITHOUT_SESSION {
+            if let Some(session) = self.session_on(date).filter(|session| session.open < before) {
+                return Some(session);
+            }
+            date = date.pred_opt()?;
+        }
+
+        None
+    }
+
+    fn to_utc(&self, date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+        // Session times are never inside a daylight saving gap or overlap, those happen at 2:00
+        self.time_zone
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .expect("session times exist in the exchange's time zone")
+            .with_timezone(&Utc)
+    }
+}
+
+// Whether day falls between from and to inclusive, going forward through the week from from
+fn is_weekday_between(day: Weekday, from: Weekday, to: Weekday) -> bool {
+    (day.days_since(from)) <= to.days_since(from)
+}
+
+fn contains_date(dates: &[(i32, u32, u32)], date: NaiveDate) -> bool {
+    dates.contains(&(date.year(), date.month(), date.day()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn opens_at_930_new_york_time_across_daylight_saving() {
+        let calendar = MarketCalendar::nyse();
+
+        // EDT, UTC-4
+        let summer = calendar.session_on(date(2025, 7, 14)).unwrap();
+        assert_eq!(summer.open, utc(2025, 7, 14, 13, 30));
+        assert_eq!(summer.close, utc(2025, 7, 14, 20, 0));
+
+        // EST, UTC-5
+        let winter = calendar.session_on(date(2025, 12, 15)).unwrap();
+        assert_eq!(winter.open, utc(2025, 12, 15, 14, 30));
+        assert_eq!(winter.close, utc(2025, 12, 15, 21, 0));
+    }
+
+    #[test]
+    fn closed_on_weekends_and_holidays() {
+        let calendar = MarketCalendar::nyse();
+
+        assert!(calendar.session_on(date(2025, 7, 12)).is_none()); // Saturday
+        assert!(calendar.session_on(date(2025, 7, 4)).is_none()); // Independence Day
+        assert!(calendar.session_on(date(2026, 4, 3)).is_none()); // Good Friday
+        assert!(!calendar.is_open(utc(2025, 11, 27, 15, 0))); // Thanksgiving, mid morning
+    }
+
+    #[test]
+    fn closes_at_1300_on_early_close_days() {
+        let calendar = MarketCalendar::nyse();
+
+        let session = calendar.session_on(date(2025, 11, 28)).unwrap();
+
+        assert!(session.is_early_close);
+        assert_eq!(session.close, utc(2025, 11, 28, 18, 0));
+        assert!(calendar.is_open(utc(2025, 11, 28, 17, 59)));
+        assert!(!calendar.is_open(utc(2025, 11, 28, 18, 0)));
+    }
+
+    #[test]
+    fn the_candle_closing_at_the_bell_belongs_to_the_session() {
+        let calendar = MarketCalendar::nyse();
+
+        assert!(calendar.session_for_candle_close(utc(2025, 7, 14, 13, 30)).is_none());
+        assert!(calendar.session_for_candle_close(utc(2025, 7, 14, 13, 45)).is_some());
+        assert!(calendar.session_for_candle_close(utc(2025, 7, 14, 20, 0)).is_some());
+        assert!(calendar.session_for_candle_close(utc(2025, 7, 14, 20, 15)).is_none());
+    }
+
+    #[test]
+    fn tags_extended_hours_sessions() {
+        let calendar = MarketCalendar::nyse();
+
+        assert_eq!(calendar.session_at(utc(2025, 7, 14, 7, 59)), None);
+        assert_eq!(calendar.session_at(utc(2025, 7, 14, 8, 0)), Some(Session::PreMarket));
+        assert_eq!(calendar.session_at(utc(2025, 7, 14, 13, 30)), Some(Session::Regular));
+        assert_eq!(calendar.session_at(utc(2025, 7, 14, 20, 0)), Some(Session::AfterHours));
+        assert_eq!(calendar.session_at(utc(2025, 7, 15, 0, 0)), None);
+
+        // The candle closing at the bell is the last regular candle
+        assert_eq!(calendar.candle_session(utc(2025, 7, 14, 20, 0)), Some(Session::Regular));
+
+        // After hours ends at 17:00 on early close days
+        assert_eq!(calendar.session_at(utc(2025, 11, 28, 21, 59)), Some(Session::AfterHours));
+        assert_eq!(calendar.session_at(utc(2025, 11, 28, 22, 0)), None);
+    }
+
+    #[test]
+    fn crypto_never_closes() {
+        let calendar = MarketCalendar::always_open();
+
+        assert!(calendar.is_open(utc(2025, 7, 12, 3, 0))); // Saturday night
+        assert!(calendar.is_open(utc(2025, 12, 25, 12, 0)));
+        assert!(calendar.session_for_candle_close(utc(2025, 7, 14, 13, 45)).is_none());
+    }
+
+    #[test]
+    fn forex_trades_sunday_evening_to_friday_evening() {
+        let calendar = MarketCalendar::forex();
+
+        // 17:00 New York is 21:00 UTC in July
+        assert!(!calendar.is_open(utc(2025, 7, 13, 20, 59))); // Sunday
+        assert!(calendar.is_open(utc(2025, 7, 13, 21, 0)));
+        assert!(calendar.is_open(utc(2025, 7, 16, 3, 0))); // Wednesday overnight
+        assert!(calendar.is_open(utc(2025, 7, 18, 20, 59))); // Friday
+        assert!(!calendar.is_open(utc(2025, 7, 18, 21, 0)));
+        assert!(!calendar.is_open(utc(2025, 7, 19, 12, 0))); // Saturday
+    }
+
+    #[test]
+    fn previous_session_skips_weekends_and_holidays() {
+        let calendar = MarketCalendar::nyse();
+
+        // Tuesday after Memorial Day, before the open
+        let session = calendar.previous_session(utc(2025, 5, 27, 12, 0)).unwrap();
+
+        assert_eq!(session.date, date(2025, 5, 23));
+    }
+
+    #[test]
+    fn sessions_are_evaluated_in_the_exchange_time_zone() {
+        assert_eq!(MarketCalendar::nyse().time_zone(), chrono_tz::America::New_York);
+        assert_eq!(MarketCalendar::always_open().time_zone(), chrono_tz::UTC);
+
+        // 01:00 UTC on Tuesday is still Monday evening in New York, and a session holds its open but not its close
+        let calendar = MarketCalendar::nyse();
+        let session = calendar.session_on(calendar.local_date(utc(2025, 7, 15, 1, 0))).unwrap();
+        assert_eq!(session.date, date(2025, 7, 14));
+        assert!(session.contains(utc(2025, 7, 14, 13, 30)));
+        assert!(!session.contains(utc(2025, 7, 14, 20, 0)));
+    }
+}
@@ -0,0 +1,68 @@
+/**
+ * NYSE closures and early closes, as (year, month, day) in New York
+ *
+ * Taken from the exchange's published holiday calendar. Dates outside the years listed here
+ * are treated as regular weekdays, so extend the tables each year.
+ */
+pub const NYSE_HOLIDAYS: &[(i32, u32, u32)] = &[
+    // 2024
+    (2024, 1, 1),   // New Year's Day
+    (2024, 1, 15),  // Martin Luther King Jr. Day
+    (2024, 2, 19),  // Washington's Birthday
+    (2024, 3, 29),  // Good Friday
+    (2024, 5, 27),  // Memorial Day
+    (2024, 6, 19),  // Juneteenth
+    (2024, 7, 4),   // Independence Day
+    (2024, 9, 2),   // Labor Day
+    (2024, 11, 28), // Thanksgiving Day
+    (2024, 12, 25), // Christmas Day
+    // 2025
+    (2025, 1, 1),   // New Year's Day
+    (2025, 1, 9),   // National Day of Mourning for President Carter
+    (2025, 1, 20),  // Martin Luther King Jr. Day
+    (2025, 2, 17),  // Washington's Birthday
+    (2025, 4, 18),  // Good Friday
+    (2025, 5, 26),  // Memorial Day
+    (2025, 6, 19),  // Juneteenth
+    (2025, 7, 4),   // Independence Day
+    (2025, 9, 1),   // Labor Day
+    (2025, 11, 27), // Thanksgiving Day
+    (2025, 12, 25), // Christmas Day
+    // 2026
+    (2026, 1, 1),   // New Year's Day
+    (2026, 1, 19),  // Martin Luther King Jr. Day
+    (2026, 2, 16),  // Washington's Birthday
+    (2026, 4, 3),   // Good Friday
+    (2026, 5, 25),  // Memorial Day
+    (2026, 6, 19),  // Juneteenth
+    (2026, 7, 3),   // Independence Day, observed
+    (2026, 9, 7),   // Labor Day
+    (2026, 11, 26), // Thanksgiving Day
+    (2026, 12, 25), // Christmas Day
+    // 2027
+    (2027, 1, 1),   // New Year's Day
+    (2027, 1, 18),  // Martin Luther King Jr. Day
+    (2027, 2, 15),  // Washington's Birthday
+    (2027, 3, 26),  // Good Friday
+    (2027, 5, 31),  // Memorial Day
+    (2027, 6, 18),  // Juneteenth, observed
+    (2027, 7, 5),   // Independence Day, observed
+    (2027, 9, 6),   // Labor Day
+    (2027, 11, 25), // Thanksgiving Day
+    (2027, 12, 24), // Christmas Day, observed
+];
+
+/**
+ * Days the NYSE closes at 13:00 New York time instead of 16:00
+ */
+pub const NYSE_EARLY_CLOSES: &[(i32, u32, u32)] = &[
+    (2024, 7, 3),   // day before Independence Day
+    (2024, 11, 29), // day after Thanksgiving
+    (2024, 12, 24), // Christmas Eve
+    (2025, 7, 3),   // day before Independence Day
+    (2025, 11, 28), // day after Thanksgiving
+    (2025, 12, 24), // Christmas Eve
+    (2026, 11, 27), // day after Thanksgiving
+    (2026, 12, 24), // Christmas Eve
+    (2027, 11, 26), // day after Thanksgiving
+];
@@ -0,0 +1,3 @@
+pub mod calendar;
+pub mod holidays;
+pub mod session;
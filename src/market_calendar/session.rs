@@ -0,0 +1,53 @@
+use std::{fmt, str::FromStr};
+
+/**
+ * Which part of a trading day a candle belongs to
+ *
+ * PreMarket: 04:00 to 09:30 New York time
+ * Regular: 09:30 to 16:00, or 13:00 on early close days
+ * AfterHours: from the close to 20:00, or 17:00 on early close days
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Session {
+    PreMarket,
+    Regular,
+    AfterHours,
+}
+
+impl Session {
+    pub const ALL: [Session; 3] = [Session::PreMarket, Session::Regular, Session::AfterHours];
+
+    pub fn is_extended(&self) -> bool {
+        *self != Session::Regular
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Session::PreMarket => "pre_market",
+            Session::Regular => "regular",
+            Session::AfterHours => "after_hours",
+        }
+    }
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/**
+ * Reads back the names written by as_str, e.g. from the stocks table
+ */
+impl FromStr for Session {
+    type Err = String;
+
+    fn from_str(session: &str) -> Result<Self, Self::Err> {
+        match session {
+            "pre_market" => Ok(Session::PreMarket),
+            "regular" => Ok(Session::Regular),
+            "after_hours" => Ok(Session::AfterHours),
+            other => Err(format!("unknown session {}", other)),
+        }
+    }
+}
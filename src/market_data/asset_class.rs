@@ -0,0 +1,110 @@
+use serde::Deserialize;
+
+// Quote currencies crypto pairs are commonly priced in, longest first so USDT is not read as USD
+const CRYPTO_QUOTE_CURRENCIES: [&str; 8] = ["USDT", "USDC", "USD", "EUR", "GBP", "JPY", "BTC", "ETH"];
+
+/**
+ * What kind of instrument a ticker is, which decides its trading calendar
+ *
+ * Equity: stocks and ETFs, traded on the NYSE calendar
+ * Crypto: traded around the clock, Polygon tickers look like X:BTCUSD
+ * Forex: traded Sunday to Friday, Polygon tickers look like C:EURUSD
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetClass {
+    Equity,
+    Crypto,
+    Forex,
+}
+
+impl AssetClass {
+    /**
+     * Reads the asset class from a Polygon ticker prefix, anything without one is an equity
+     */
+    pub fn infer(symbol: &str) -> AssetClass {
+        if symbol.starts_with("X:") {
+            return AssetClass::Crypto;
+        }
+        if symbol.starts_with("C:") {
+            return AssetClass::Forex;
+        }
+        AssetClass::Equity
+    }
+
+    pub fn polygon_prefix(&self) -> Option<&'static str> {
+        match self {
+            AssetClass::Equity => None,
+            AssetClass::Crypto => Some("X:"),
+            AssetClass::Forex => Some("C:"),
+        }
+    }
+
+    /**
+     * The Polygon ticker for a symbol, adding the prefix when it was left off, e.g. BTCUSD becomes X:BTCUSD
+     */
+    pub fn polygon_symbol(&self, symbol: &str) -> String {
+        match self.polygon_prefix() {
+            Some(prefix) if !symbol.starts_with(prefix) && AssetClass::infer(symbol) == AssetClass::Equity => {
+                format!("{}{}", prefix, symbol)
+            }
+            _ => symbol.to_string(),
+        }
+    }
+}
+
+/**
+ * The symbol without its Polygon prefix, e.g. X:BTCUSD becomes BTCUSD
+ */
+pub fn bare_symbol(symbol: &str) -> &str {
+    symbol
+        .strip_prefix("X:")
+        .or_else(|| symbol.strip_prefix("C:"))
+        .unwrap_or(symbol)
+}
+
+/**
+ * How a ticker is shown to people, pairs are split into base and quote, e.g. X:BTCUSD becomes BTC/USD.
+ * Twelve Data names pairs the same way.
+ */
+pub fn display_name(symbol: &str) -> String {
+    let bare = bare_symbol(symbol);
+
+    let quote = match AssetClass::infer(symbol) {
+        AssetClass::Equity => None,
+        AssetClass::Forex => (bare.len() == 6).then(|| &bare[3..]),
+        AssetClass::Crypto => CRYPTO_QUOTE_CURRENCIES
+            .iter()
+            .copied()
+            .find(|quote| bare.len() > quote.len() && bare.ends_with(quote)),
+    };
+
+    match quote {
+        Some(quote) => format!("{}/{}", &bare[..bare.len() - quote.len()], quote),
+        None => bare.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infers_the_asset_class_from_the_polygon_prefix() {
+        assert_eq!(AssetClass::infer("QQQ"), AssetClass::Equity);
+        assert_eq!(AssetClass::infer("X:BTCUSD"), AssetClass::Crypto);
+        assert_eq!(AssetClass::infer("C:EURUSD"), AssetClass::Forex);
+
+        assert_eq!(AssetClass::Crypto.polygon_symbol("BTCUSD"), "X:BTCUSD");
+        assert_eq!(AssetClass::Crypto.polygon_symbol("X:BTCUSD"), "X:BTCUSD");
+        assert_eq!(AssetClass::Equity.polygon_symbol("QQQ"), "QQQ");
+    }
+
+    #[test]
+    fn splits_pairs_for_display() {
+        assert_eq!(display_name("QQQ"), "QQQ");
+        assert_eq!(display_name("X:BTCUSD"), "BTC/USD");
+        assert_eq!(display_name("X:ETHUSDT"), "ETH/USDT");
+        assert_eq!(display_name("C:EURUSD"), "EUR/USD");
+    }
+}
@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+
+use crate::{
+    market_data::{fetch_error::FetchError, market_data_provider::{MarketDataProvider, RateLimitHints}},
+    polygon_api::stock_data_response::PriceDatum,
+};
+
+/**
+ * Sends every request to the primary provider, and retries it against the fallback when the primary fails
+ */
+pub struct FallbackProvider {
+    primary: Box<dyn MarketDataProvider>,
+    fallback: Box<dyn MarketDataProvider>,
+    name: String,
+}
+
+impl FallbackProvider {
+    pub fn new(primary: Box<dyn MarketDataProvider>, fallback: Box<dyn MarketDataProvider>) -> Self {
+        let name = format!("{} (fallback {})", primary.name(), fallback.name());
+        FallbackProvider { primary, fallback, name }
+    }
+}
+
+#[async_trait]
+impl MarketDataProvider for FallbackProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn rate_limit_hints(&self) -> RateLimitHints {
+        self.primary.rate_limit_hints()
+    }
+
+    async fn fetch_bars(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        timestamp_from: i64,
+        timestamp_to: i64,
+    ) -> Result<Vec<PriceDatum>, FetchError> {
+        match self.primary.fetch_bars(ticker, timeframe, timestamp_from, timestamp_to).await {
+            Ok(bars) => Ok(bars),
+            Err(e) => {
+                eprintln!("{} failed for {}, falling back to {}: {}", self.primary.name(), ticker, self.fallback.name(), e);
+                self.fallback.fetch_bars(ticker, timeframe, timestamp_from, timestamp_to).await
+            }
+        }
+    }
+
+    async fn fetch_latest_bar(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        timestamp_to: i64,
+    ) -> Result<PriceDatum, FetchError> {
+        match self.primary.fetch_latest_bar(ticker, timeframe, timestamp_to).await {
+            Ok(bar) => Ok(bar),
+            Err(e) => {
+                eprintln!("{} failed for {}, falling back to {}: {}", self.primary.name(), ticker, self.fallback.name(), e);
+                self.fallback.fetch_latest_bar(ticker, timeframe, timestamp_to).await
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::{error::Error, fmt, time::Duration};
+
+/**
+ * Why a provider could not hand back candles
+ *
+ * HttpStatus: the provider answered with a non success status not covered below
+ * RateLimited: the provider rejected the request for going over the plan's limits
+ * AuthFailure: the API key is missing, wrong or does not cover the request
+ * EmptyResults: the request succeeded but there were no candles in the window
+ * Decode: the body was not the JSON we expected
+ * Transport: the request never got a response, e.g. DNS, connection or timeout errors
+ * Unsupported: the provider cannot serve this request at all, e.g. an unknown timeframe
+ * Timeout: the fetch, including any retries, did not finish in time
+ */
+#[derive(Debug)]
+pub enum FetchError {
+    HttpStatus { status: u16, message: String },
+    RateLimited { retry_after: Option<Duration> },
+    AuthFailure { status: u16, message: String },
+    EmptyResults,
+    Decode(String),
+    Transport(reqwest::Error),
+    Unsupported(String),
+    Timeout(Duration),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::HttpStatus { status, message } => write!(f, "responded with {}: {}", status, message),
+            FetchError::RateLimited { retry_after: Some(retry_after) } => {
+                write!(f, "rate limited, retry after {}s", retry_after.as_secs())
+            }
+            FetchError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            FetchError::AuthFailure { status, message } => write!(f, "authentication failed ({}): {}", status, message),
+            FetchError::EmptyResults => write!(f, "no candles in the requested window"),
+            FetchError::Decode(message) => write!(f, "could not decode response: {}", message),
+            FetchError::Transport(e) => write!(f, "request failed: {}", e),
+            FetchError::Unsupported(message) => write!(f, "unsupported request: {}", message),
+            FetchError::Timeout(timeout) => write!(f, "timed out after {}s", timeout.as_secs()),
+        }
+    }
+}
+
+impl Error for FetchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FetchError::Transport(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_decode() {
+            return FetchError::Decode(e.to_string());
+        }
+        FetchError::Transport(e)
+    }
+}
+
+impl From<serde_json::Error> for FetchError {
+    fn from(e: serde_json::Error) -> Self {
+        FetchError::Decode(e.to_string())
+    }
+}
+
+impl FetchError {
+    /**
+     * Maps a non success status code to the matching variant
+     *
+     * @retry_after: the Retry-After header in seconds, if the provider sent one
+     */
+    pub fn from_status(status: u16, message: String, retry_after: Option<Duration>) -> FetchError {
+        match status {
+            401 | 403 => FetchError::AuthFailure { status, message },
+            429 => FetchError::RateLimited { retry_after },
+            _ => FetchError::HttpStatus { status, message },
+        }
+    }
+}
+
+/**
+ * Reads a Retry-After header given in seconds, the form Polygon and Twelve Data use
+ */
+pub fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
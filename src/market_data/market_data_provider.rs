@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+
+use crate::{
+    data_polling::constants::MINUTES_TO_MILIS,
+    market_data::fetch_error::FetchError,
+    polygon_api::stock_data_response::PriceDatum,
+};
+
+/**
+ * How hard a provider can be polled before it starts rejecting requests
+ *
+ * @requests_per_minute: sustained request rate allowed by the plan we are on
+ * @burst: how many requests can be sent back to back before the rate applies
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitHints {
+    pub requests_per_minute: u32,
+    pub burst: u32,
+}
+
+/**
+ * One response's worth of candles
+ *
+ * @field bars: the candles in this response, oldest first
+ * @field next_page: where the provider said the window continues, None on the last page
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarsPage {
+    pub bars: Vec<PriceDatum>,
+    pub next_page: Option<String>,
+}
+
+/**
+ * A source of OHLCV candles. The polling loop only talks to this trait,
+ * so vendors can be swapped or chained without touching the loop.
+ *
+ * Timestamps are unix milliseconds and timeframes are candle sizes in minutes.
+ */
+#[async_trait]
+pub trait MarketDataProvider: Send + Sync {
+    /**
+     * Short name used in logs, e.g. polygon
+     */
+    fn name(&self) -> &str;
+
+    fn rate_limit_hints(&self) -> RateLimitHints;
+
+    /**
+     * Every candle for the ticker that starts between timestamp_from and timestamp_to, oldest first
+     * A window without any candles is FetchError::EmptyResults rather than an empty Vec.
+     */
+    async fn fetch_bars(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        timestamp_from: i64,
+        timestamp_to: i64,
+    ) -> Result<Vec<PriceDatum>, FetchError>;
+
+    /**
+     * One request of fetch_bars. Providers that split a window over several responses override this,
+     * so each follow up request can be rate limited on its own. The rest answer the whole window at once.
+     *
+     * @param page: the next_page of the previous page, None for the first one
+     */
+    async fn fetch_bars_page(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        timestamp_from: i64,
+        timestamp_to: i64,
+        _page: Option<&str>,
+    ) -> Result<BarsPage, FetchError> {
+        let bars = self.fetch_bars(ticker, timeframe, timestamp_from, timestamp_to).await?;
+
+        Ok(BarsPage { bars, next_page: None })
+    }
+
+    /**
+     * The most recent candle at or before timestamp_to
+     */
+    async fn fetch_latest_bar(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        timestamp_to: i64,
+    ) -> Result<PriceDatum, FetchError> {
+        let timestamp_from = timestamp_to - timeframe as i64 * MINUTES_TO_MILIS;
+        let bars = self.fetch_bars(ticker, timeframe, timestamp_from, timestamp_to).await?;
+
+        bars.into_iter().next_back().ok_or(FetchError::EmptyResults)
+    }
+}
+
+/**
+ * Follows a provider's pages until the window is complete
+ *
+ * returns every candle in the window oldest first, FetchError::EmptyResults when no page had any
+ */
+pub async fn fetch_all_pages(
+    provider: &dyn MarketDataProvider,
+    ticker: &str,
+    timeframe: u32,
+    timestamp_from: i64,
+    timestamp_to: i64,
+) -> Result<Vec<PriceDatum>, FetchError> {
+    let mut bars = Vec::new();
+    let mut page = None;
+
+    loop {
+        let BarsPage { bars: page_bars, next_page } = provider
+            .fetch_bars_page(ticker, timeframe, timestamp_from, timestamp_to, page.as_deref())
+            .await?;
+        bars.extend(page_bars);
+
+        page = match next_page {
+            Some(next_page) => Some(next_page),
+            None => break,
+        };
+    }
+
+    if bars.is_empty() {
+        return Err(FetchError::EmptyResults);
+    }
+
+    Ok(bars)
+}
@@ -0,0 +1,8 @@
+pub mod asset_class;
+pub mod fallback_provider;
+pub mod fetch_error;
+pub mod market_data_provider;
+pub mod rate_limited_provider;
+pub mod rate_limiter;
+pub mod retry_policy;
+pub mod select_provider;
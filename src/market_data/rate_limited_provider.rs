@@ -0,0 +1,245 @@
+use async_trait::async_trait;
+use tokio::time::sleep;
+
+use crate::{
+    market_data::{
+        fetch_error::FetchError,
+        market_data_provider::{fetch_all_pages, BarsPage, MarketDataProvider, RateLimitHints},
+        rate_limiter::RateLimiter,
+        retry_policy::RetryPolicy,
+    },
+    polygon_api::stock_data_response::PriceDatum,
+};
+
+/**
+ * Wraps a provider so every request, including each follow up page of a long window,
+ * first takes a token from its rate limiter, and transient failures are retried with jittered backoff.
+ *
+ * A 429 pauses the limiter for the provider's Retry-After, or the backoff when it sent none,
+ * so other tickers waiting on the same provider back off too instead of burning more requests.
+ */
+pub struct RateLimitedProvider {
+    inner: Box<dyn MarketDataProvider>,
+    rate_limit: RateLimitHints,
+    limiter: RateLimiter,
+    retry_policy: RetryPolicy,
+}
+
+impl RateLimitedProvider {
+    pub fn new(inner: Box<dyn MarketDataProvider>, rate_limit: RateLimitHints, retry_policy: RetryPolicy) -> Self {
+        RateLimitedProvider {
+            inner,
+            rate_limit,
+            limiter: RateLimiter::new(rate_limit.requests_per_minute, rate_limit.burst),
+            retry_policy,
+        }
+    }
+}
+
+#[async_trait]
+impl MarketDataProvider for RateLimitedProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn rate_limit_hints(&self) -> RateLimitHints {
+        self.rate_limit
+    }
+
+    async fn fetch_bars(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        timestamp_from: i64,
+        timestamp_to: i64,
+    ) -> Result<Vec<PriceDatum>, FetchError> {
+        fetch_all_pages(self, ticker, timeframe, timestamp_from, timestamp_to).await
+    }
+
+    async fn fetch_bars_page(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        timestamp_from: i64,
+        timestamp_to: i64,
+        page: Option<&str>,
+    ) -> Result<BarsPage, FetchError> {
+        let mut attempt = 1;
+
+        loop {
+            self.limiter.acquire().await;
+
+            let err = match self.inner.fetch_bars_page(ticker, timeframe, timestamp_from, timestamp_to, page).await {
+                Ok(page) => return Ok(page),
+                Err(e) => e,
+            };
+
+            if attempt >= self.retry_policy.max_attempts || !RetryPolicy::is_retryable(&err) {
+                return Err(err);
+            }
+
+            let backoff = self.retry_policy.backoff(attempt);
+            match err {
+                // Without a Retry-After the backoff is the pause, the emptied bucket still holds everyone to the plan's rate after it
+                FetchError::RateLimited { retry_after } => {
+                    let pause = retry_after.unwrap_or(backoff);
+                    eprintln!("{} rate limited {}, retrying in {}ms", self.name(), ticker, pause.as_millis());
+                    self.limiter.pause_for(pause).await;
+                }
+                _ => {
+                    eprintln!(
+                        "{} failed for {} (attempt {}/{}), retrying in {}ms: {}",
+                        self.name(), ticker, attempt, self.retry_policy.max_attempts, backoff.as_millis(), err
+                    );
+                    sleep(backoff).await;
+                }
+            }
+
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use tokio::time::Instant;
+
+    use crate::test_fixtures::bar;
+
+    use super::*;
+
+    /**
+     * Answers every window in three pages of one candle each
+     */
+    struct PagedProvider;
+
+    #[async_trait]
+    impl MarketDataProvider for PagedProvider {
+        fn name(&self) -> &str {
+            "paged"
+        }
+
+        fn rate_limit_hints(&self) -> RateLimitHints {
+            RateLimitHints { requests_per_minute: 60, burst: 1 }
+        }
+
+        async fn fetch_bars(&self, ticker: &str, timeframe: u32, timestamp_from: i64, timestamp_to: i64) -> Result<Vec<PriceDatum>, FetchError> {
+            fetch_all_pages(self, ticker, timeframe, timestamp_from, timestamp_to).await
+        }
+
+        async fn fetch_bars_page(&self, _ticker: &str, _timeframe: u32, _timestamp_from: i64, _timestamp_to: i64, page: Option<&str>) -> Result<BarsPage, FetchError> {
+            let (timestamp, next_page) = match page {
+                None => (1, Some("2")),
+                Some("2") => (2, Some("3")),
+                _ => (3, None),
+            };
+            Ok(BarsPage { bars: vec![bar(timestamp, 100.0)], next_page: next_page.map(str::to_string) })
+        }
+    }
+
+    /**
+     * Fails with the queued errors one call at a time, then answers with a single candle
+     *
+     * @field calls: how many requests reached the provider, shared with the test
+     */
+    struct FlakyProvider {
+        failures: Mutex<Vec<FetchError>>,
+        calls: Arc<Mutex<u32>>,
+    }
+
+    impl FlakyProvider {
+        fn failing_with(mut failures: Vec<FetchError>) -> (Self, Arc<Mutex<u32>>) {
+            failures.reverse();
+            let calls = Arc::new(Mutex::new(0));
+            (FlakyProvider { failures: Mutex::new(failures), calls: calls.clone() }, calls)
+        }
+    }
+
+    #[async_trait]
+    impl MarketDataProvider for FlakyProvider {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        fn rate_limit_hints(&self) -> RateLimitHints {
+            RateLimitHints { requests_per_minute: 60, burst: 5 }
+        }
+
+        async fn fetch_bars(&self, _ticker: &str, _timeframe: u32, _timestamp_from: i64, _timestamp_to: i64) -> Result<Vec<PriceDatum>, FetchError> {
+            *self.calls.lock().unwrap() += 1;
+            match self.failures.lock().unwrap().pop() {
+                Some(failure) => Err(failure),
+                None => Ok(vec![bar(1, 100.0)]),
+            }
+        }
+    }
+
+    fn retry_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn every_page_waits_for_its_own_token() {
+        let provider = RateLimitedProvider::new(Box::new(PagedProvider), RateLimitHints { requests_per_minute: 60, burst: 1 }, retry_policy());
+        let start = Instant::now();
+
+        let bars = provider.fetch_bars("QQQ", 15, 0, 10).await.unwrap();
+
+        assert_eq!(bars.iter().map(|bar| bar.timestamp).collect::<Vec<_>>(), vec![1, 2, 3]);
+        // One request a second with no burst to spare, so the follow up pages each wait a second
+        assert!(start.elapsed() >= Duration::from_secs(2));
+        assert!(start.elapsed() < Duration::from_secs(3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_rate_limit_without_retry_after_still_empties_the_shared_bucket() {
+        let (flaky, calls) = FlakyProvider::failing_with(vec![FetchError::RateLimited { retry_after: None }]);
+        let provider = RateLimitedProvider::new(Box::new(flaky), RateLimitHints { requests_per_minute: 60, burst: 5 }, retry_policy());
+        let start = Instant::now();
+
+        let bars = provider.fetch_bars("QQQ", 15, 0, 10).await.unwrap();
+
+        assert_eq!(bars.len(), 1);
+        assert_eq!(*calls.lock().unwrap(), 2);
+        // Four tokens were left in the burst, the retry still waited for a fresh one
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_rate_limit_pauses_for_its_retry_after() {
+        let (flaky, _) = FlakyProvider::failing_with(vec![FetchError::RateLimited { retry_after: Some(Duration::from_secs(20)) }]);
+        let provider = RateLimitedProvider::new(Box::new(flaky), RateLimitHints { requests_per_minute: 60, burst: 5 }, retry_policy());
+        let start = Instant::now();
+
+        provider.fetch_bars("QQQ", 15, 0, 10).await.unwrap();
+
+        assert_eq!(start.elapsed(), Duration::from_secs(20));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_after_max_attempts_and_on_permanent_failures() {
+        let server_error = || FetchError::HttpStatus { status: 502, message: String::new() };
+        let (flaky, calls) = FlakyProvider::failing_with(vec![server_error(), server_error(), server_error(), server_error()]);
+        let provider = RateLimitedProvider::new(Box::new(flaky), RateLimitHints { requests_per_minute: 60, burst: 5 }, retry_policy());
+
+        let err = provider.fetch_bars("QQQ", 15, 0, 10).await.unwrap_err();
+        assert!(matches!(err, FetchError::HttpStatus { status: 502, .. }));
+        assert_eq!(*calls.lock().unwrap(), 3);
+
+        let (flaky, calls) = FlakyProvider::failing_with(vec![FetchError::AuthFailure { status: 401, message: String::new() }]);
+        let provider = RateLimitedProvider::new(Box::new(flaky), RateLimitHints { requests_per_minute: 60, burst: 5 }, retry_policy());
+
+        let err = provider.fetch_bars("QQQ", 15, 0, 10).await.unwrap_err();
+        assert!(matches!(err, FetchError::AuthFailure { .. }));
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
+}
@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use tokio::{sync::Mutex, time::{sleep, Instant}};
+
+/**
+ * Client side token bucket, so we stay under a provider's plan limits instead of finding out from 429s
+ *
+ * Holds up to burst tokens and refills at requests_per_minute. Every request takes one token,
+ * waiting for the next refill when the bucket is empty.
+ */
+pub struct RateLimiter {
+    bucket: Mutex<TokenBucket>,
+}
+
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+    // Set when the provider tells us to back off, no tokens are handed out before it
+    paused_until: Option<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: u32, burst: u32) -> Self {
+        let capacity = burst.max(1) as f64;
+        RateLimiter {
+            bucket: Mutex::new(TokenBucket {
+                capacity,
+                tokens: capacity,
+                refill_per_sec: requests_per_minute.max(1) as f64 / 60.0,
+                last_refill: Instant::now(),
+                paused_until: None,
+            }),
+        }
+    }
+
+    /**
+     * Waits until a request may be sent, then takes a token for it
+     */
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().await;
+                let now = Instant::now();
+                bucket.refill(now);
+
+                match bucket.paused_until {
+                    Some(paused_until) if paused_until > now => paused_until - now,
+                    _ if bucket.tokens >= 1.0 => {
+                        bucket.tokens -= 1.0;
+                        return;
+                    }
+                    _ => Duration::from_secs_f64((1.0 - bucket.tokens) / bucket.refill_per_sec),
+                }
+            };
+
+            sleep(wait).await;
+        }
+    }
+
+    /**
+     * Stops handing out tokens for the given duration and empties the bucket,
+     * used whenever the provider answers 429
+     */
+    pub async fn pause_for(&self, duration: Duration) {
+        let mut bucket = self.bucket.lock().await;
+        let now = Instant::now();
+        let paused_until = now + duration;
+
+        // Refilled first, so time spent idle before the 429 is not handed out again after it
+        bucket.refill(now);
+        bucket.tokens = 0.0;
+        bucket.paused_until = Some(bucket.paused_until.map_or(paused_until, |current| current.max(paused_until)));
+    }
+}
+
+impl TokenBucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Time is paused, so every wait below is exact and takes no real time
+
+    #[tokio::test(start_paused = true)]
+    async fn hands_out_the_burst_then_waits_for_refills() {
+        let limiter = RateLimiter::new(60, 3);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refills_while_idle_up_to_the_burst() {
+        let limiter = RateLimiter::new(60, 2);
+        limiter.acquire().await;
+        limiter.acquire().await;
+
+        sleep(Duration::from_secs(10)).await;
+        let start = Instant::now();
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_pause_holds_every_request_until_it_is_over() {
+        let limiter = RateLimiter::new(60, 5);
+        let start = Instant::now();
+
+        limiter.pause_for(Duration::from_secs(30)).await;
+        limiter.acquire().await;
+
+        assert_eq!(start.elapsed(), Duration::from_secs(30));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_short_pause_after_idling_still_empties_the_bucket() {
+        let limiter = RateLimiter::new(60, 5);
+        sleep(Duration::from_secs(60)).await;
+        let start = Instant::now();
+
+        limiter.pause_for(Duration::from_millis(100)).await;
+        limiter.acquire().await;
+
+        // The pause refilled a tenth of a token, the rest comes at the plan's rate
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_shorter_pause_does_not_cut_a_longer_one_short() {
+        let limiter = RateLimiter::new(60, 5);
+        let start = Instant::now();
+
+        limiter.pause_for(Duration::from_secs(30)).await;
+        limiter.pause_for(Duration::from_secs(5)).await;
+        limiter.acquire().await;
+
+        assert_eq!(start.elapsed(), Duration::from_secs(30));
+    }
+}
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::market_data::fetch_error::FetchError;
+
+/**
+ * When and how long to wait before retrying a failed fetch
+ *
+ * @max_attempts: total attempts including the first one
+ * @base_delay: the backoff before the first retry, doubled for every retry after
+ * @max_delay: upper bound on any single backoff
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /**
+     * Transient failures are worth retrying: server errors, timeouts, dropped connections and rate limiting.
+     * Auth failures, bad requests and empty windows will fail the same way again.
+     */
+    pub fn is_retryable(error: &FetchError) -> bool {
+        match error {
+            FetchError::HttpStatus { status, .. } => *status >= 500,
+            FetchError::RateLimited { .. } => true,
+            FetchError::Transport(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            FetchError::AuthFailure { .. }
+            | FetchError::EmptyResults
+            | FetchError::Decode(_)
+            | FetchError::Unsupported(_)
+            | FetchError::Timeout(_) => false,
+        }
+    }
+
+    /**
+     * Full jitter exponential backoff: a random delay between zero and base_delay * 2^(attempt - 1), capped at max_delay
+     *
+     * @attempt: the attempt that just failed, starting at 1
+     */
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let ceiling = self.base_delay.saturating_mul(1 << exponent).min(self.max_delay);
+
+        Duration::from_secs_f64(rand::rng().random_range(0.0..=ceiling.as_secs_f64()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retry_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(4),
+        }
+    }
+
+    #[test]
+    fn retries_transient_failures_only() {
+        let http_status = |status| FetchError::HttpStatus { status, message: String::new() };
+
+        assert!(RetryPolicy::is_retryable(&http_status(500)));
+        assert!(RetryPolicy::is_retryable(&http_status(503)));
+        assert!(RetryPolicy::is_retryable(&FetchError::RateLimited { retry_after: None }));
+        assert!(RetryPolicy::is_retryable(&FetchError::RateLimited { retry_after: Some(Duration::from_secs(60)) }));
+
+        assert!(!RetryPolicy::is_retryable(&http_status(400)));
+        assert!(!RetryPolicy::is_retryable(&http_status(404)));
+        assert!(!RetryPolicy::is_retryable(&FetchError::AuthFailure { status: 401, message: String::new() }));
+        assert!(!RetryPolicy::is_retryable(&FetchError::EmptyResults));
+        assert!(!RetryPolicy::is_retryable(&FetchError::Decode("missing field".to_string())));
+        assert!(!RetryPolicy::is_retryable(&FetchError::Unsupported("7 minute candles".to_string())));
+        assert!(!RetryPolicy::is_retryable(&FetchError::Timeout(Duration::from_secs(30))));
+    }
+
+    #[test]
+    fn backoff_doubles_per_attempt_up_to_the_cap() {
+        let retry_policy = retry_policy();
+
+        // Full jitter, so only the ceiling each draw stays under is fixed
+        for (attempt, ceiling) in [(1, 500), (2, 1_000), (3, 2_000), (4, 4_000), (5, 4_000), (40, 4_000)] {
+            let backoffs: Vec<Duration> = (0..200).map(|_| retry_policy.backoff(attempt)).collect();
+
+            assert!(backoffs.iter().all(|backoff| *backoff <= Duration::from_millis(ceiling)), "attempt {}", attempt);
+            // Spread over the whole range rather than stuck at either end
+            assert!(backoffs.iter().any(|backoff| *backoff > Duration::from_millis(ceiling / 2)), "attempt {}", attempt);
+            assert!(backoffs.iter().any(|backoff| *backoff < Duration::from_millis(ceiling / 2)), "attempt {}", attempt);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::{env, fmt, sync::Arc, time::Duration};
+
+use crate::{
+    config::monitor_config::{ProviderConfig, ProviderName},
+    market_data::{
+        fallback_provider::FallbackProvider,
+        market_data_provider::{MarketDataProvider, RateLimitHints},
+        rate_limited_provider::RateLimitedProvider,
+        retry_policy::RetryPolicy,
+    },
+    polygon_api::polygon_provider::PolygonProvider,
+    twelve_data_api::twelve_data_provider::TwelveDataProvider,
+};
+
+/**
+ * A provider named in the config could not be constructed
+ */
+#[derive(Debug)]
+pub enum ProviderSetupError {
+    MissingApiKey { provider: ProviderName, env_var: &'static str },
+}
+
+impl fmt::Display for ProviderSetupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProviderSetupError::MissingApiKey { provider, env_var } => {
+                write!(f, "{:?} is configured as a provider but {} is not set", provider, env_var)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProviderSetupError {}
+
+/**
+ * Builds the provider described by the [provider] section of the config,
+ * wrapping it with its fallback when one is configured.
+ *
+ * Each provider gets its own rate limiter, so a fallback does not spend the primary's budget.
+ */
+pub fn select_provider(provider_config: &ProviderConfig) -> Result<Arc<dyn MarketDataProvider>, ProviderSetupError> {
+    let primary = build_rate_limited_provider(provider_config, provider_config.name)?;
+
+    match provider_config.fallback {
+        Some(fallback) => Ok(Arc::new(FallbackProvider::new(
+            primary,
+            build_rate_limited_provider(provider_config, fallback)?,
+        ))),
+        None => Ok(Arc::from(primary)),
+    }
+}
+
+/**
+ * Polygon on its own, with the rate limit and retry settings from the config, whichever provider polls live
+ */
+pub fn select_polygon_provider(provider_config: &ProviderConfig) -> Result<Arc<dyn MarketDataProvider>, ProviderSetupError> {
+    Ok(Arc::from(build_rate_limited_provider(provider_config, ProviderName::Polygon)?))
+}
+
+fn build_rate_limited_provider(
+    provider_config: &ProviderConfig,
+    provider_name: ProviderName,
+) -> Result<Box<dyn MarketDataProvider>, ProviderSetupError> {
+    let provider = build_provider(provider_name)?;
+
+    let rate_limit = match provider_config.rate_limits.get(&provider_name) {
+        Some(rate_limit) => RateLimitHints {
+            requests_per_minute: rate_limit.requests_per_minute,
+            burst: rate_limit.burst.unwrap_or(rate_limit.requests_per_minute),
+        },
+        None => provider.rate_limit_hints(),
+    };
+
+    let retry_policy = RetryPolicy {
+        max_attempts: provider_config.retry.max_attempts,
+        base_delay: Duration::from_millis(provider_config.retry.base_delay_ms),
+        max_delay: Duration::from_millis(provider_config.retry.max_delay_ms),
+    };
+
+    Ok(Box::new(RateLimitedProvider::new(provider, rate_limit, retry_policy)))
+}
+
+fn build_provider(provider_name: ProviderName) -> Result<Box<dyn MarketDataProvider>, ProviderSetupError> {
+    match provider_name {
+        ProviderName::Polygon => {
+            let api_key = read_api_key(provider_name, "POLYGON_API_KEY")?;
+            Ok(Box::new(PolygonProvider::new(api_key)))
+        }
+        ProviderName::TwelveData => {
+            let api_key = read_api_key(provider_name, "TWELVE_DATA_API_KEY")?;
+            Ok(Box::new(TwelveDataProvider::new(api_key)))
+        }
+    }
+}
+
+fn read_api_key(provider: ProviderName, env_var: &'static str) -> Result<String, ProviderSetupError> {
+    env::var(env_var).map_err(|_| ProviderSetupError::MissingApiKey { provider, env_var })
+}
@@ -0,0 +1,15 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+
+use crate::data_polling::alert_cluster::AlertRecord;
+
+pub type NotifyError = Box<dyn Error + Send + Sync>;
+
+/**
+ * Where fired alerts go: email when running live, a report when replaying history
+ */
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, alert: &AlertRecord) -> Result<(), NotifyError>;
+}
@@ -0,0 +1,37 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::{
+    data_polling::alert_cluster::AlertRecord,
+    notifier::alert_notifier::{NotifyError, Notifier},
+};
+
+/**
+ * Keeps every alert in memory instead of sending it, so a replay can report what would have been sent
+ */
+#[derive(Default)]
+pub struct CapturedNotifier {
+    alerts: Mutex<Vec<AlertRecord>>,
+}
+
+impl CapturedNotifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Every alert captured so far, in the order they fired
+     */
+    pub fn alerts(&self) -> Vec<AlertRecord> {
+        self.alerts.lock().expect("captured alerts lock is not poisoned").clone()
+    }
+}
+
+#[async_trait]
+impl Notifier for CapturedNotifier {
+    async fn notify(&self, alert: &AlertRecord) -> Result<(), NotifyError> {
+        self.alerts.lock().expect("captured alerts lock is not poisoned").push(alert.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+
+use crate::{
+    aws_ses::send_email::send_email,
+    data_polling::alert_cluster::AlertRecord,
+    notifier::alert_notifier::{NotifyError, Notifier},
+};
+
+/**
+ * Sends an email through SES for every alert
+ */
+pub struct EmailNotifier;
+
+#[async_trait]
+impl Notifier for EmailNotifier {
+    async fn notify(&self, _alert: &AlertRecord) -> Result<(), NotifyError> {
+        send_email().await?;
+        Ok(())
+    }
+}
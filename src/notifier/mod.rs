@@ -0,0 +1,3 @@
+pub mod alert_notifier;
+pub mod captured_notifier;
+pub mod email_notifier;
@@ -1,77 +1,92 @@
-use reqwest::get;
-use std::error::Error;
-use crate::polygon_api::{ stock_data_response::*, stock::{StockData,StockDatum}};
-
-
-//TODO refactor to use twelvedata's api
-pub const NULL_STOCK_DATA_RESPONSE: PriceDatum = PriceDatum {
-    timestamp: 0,
-    volume: 0,
-    high: -1.0,
-    low: -1.0,
-    close: -1.0,
-    open: -1.0,
+use reqwest::Client;
+use crate::{
+    market_data::{
+        fetch_error::{parse_retry_after, FetchError},
+        market_data_provider::BarsPage,
+    },
+    polygon_api::stock_data_response::*,
 };
 
-pub const EMPTY_RESPONSE_DOUBLE_FLAG: (f64, f64, f64, f64) = (0.0, 0.0, 0.0, 0.0);
+pub const POLYGON_BASE_URL: &str = "https://api.polygon.io";
+
+// Largest page Polygon's aggregates endpoint will return
+const MAX_PAGE_SIZE: u32 = 50_000;
 
 /**
- * 
+ * The aggregates request for a ticker between timestamp_from and timestamp_to, without the API key
  */
-pub async fn fetch_data(
-    ticker: &str,
-    timeframe: &u32,
-    timestamp_from: &i64,
-    timestamp_to: &i64,
-    api_key: &str
-) -> Result<PriceDatum, Box<dyn Error>> {
-    //Initialize with a default falsey response
-
-    let request_url = format!(
-        "https://api.polygon.io/v2/aggs/ticker/{}/range/{}/minute/{}/{}/?apiKey={}",
+pub fn aggregates_url(base_url: &str, ticker: &str, timeframe: u32, timestamp_from: i64, timestamp_to: i64) -> String {
+    format!(
+        "{}/v2/aggs/ticker/{}/range/{}/minute/{}/{}/?sort=asc&limit={}",
+        base_url,
         ticker,
         timeframe,
         timestamp_from,
         timestamp_to,
-        api_key
-    );
+        MAX_PAGE_SIZE
+    )
+}
+
+/**
+ * Fetches one page of aggregates from Polygon.io
+ *
+ * Polygon caps how many aggregates one response holds and links the rest through next_url,
+ * which comes back as the page's next_page so every follow up request can be rate limited on its own.
+ *
+ * @param request_url: aggregates_url for the first page, the previous page's next_page after that
+ * returns the page's aggregates oldest first, empty when Polygon had no results
+ */
+pub async fn fetch_data(client: &Client, request_url: &str, api_key: &str) -> Result<BarsPage, FetchError> {
+    // Neither the first url nor next_url carry the key
+    let request_url = format!("{}&apiKey={}", request_url, api_key);
     // println!("{:?}", request_url.clone()); // Debugger variable
-    let res = get(request_url).await?;
 
-    let is_res_ok = res.status().is_success();
-    // let raw_json: serde_json::Value = res.json().await?;
-    // println!("Raw JSON response: {:?}", raw_json); // debugger statement
-    // return Ok(NULL_STOCK_DATA_RESPONSE);
+    let polygon_response = fetch_page(client, &request_url).await?;
 
-    let body: Result<PolygonResponse, reqwest::Error> = res.json::<PolygonResponse>().await;
+    Ok(BarsPage {
+        bars: format_price_data(&polygon_response),
+        next_page: polygon_response.next_url,
+    })
+}
 
-    let is_validated_body: bool = body.is_ok() && !body.as_ref().unwrap().results.is_some();
+/*
+ * Requests one page of aggregates
+ */
+async fn fetch_page(client: &Client, request_url: &str) -> Result<PolygonResponse, FetchError> {
+    let res = client.get(request_url).send().await?;
 
-    if !is_res_ok || is_validated_body {
-        let reason =  if !is_res_ok { "invalid respond"} else { "corrupt body"};
-        println!("Error collecting data due to issue connecting to data provider for symbol {} due to {}", ticker, reason);
-        return Ok(NULL_STOCK_DATA_RESPONSE);
-    } 
+    let status = res.status();
+    if !status.is_success() {
+        let retry_after = parse_retry_after(res.headers());
+        let message = res.text().await.unwrap_or_default();
+        return Err(FetchError::from_status(status.as_u16(), message, retry_after));
+    }
 
-  
-    Ok( format_price_datum(&body.unwrap()))
+    let body = res.bytes().await?;
+    // println!("Raw JSON response: {:?}", String::from_utf8_lossy(&body)); // debugger statement
+    Ok(serde_json::from_slice(&body)?)
 }
 
 /*
- * Takes the last entry in the PolygonResponse, returns it as a PriceDatum type
+ * Takes every entry in the PolygonResponse, returns them as PriceDatum
  *
- * returns a single PriceDatum
+ * returns a Vec of PriceDatum, oldest first, empty when Polygon had no results
  */
-fn format_price_datum(polygon_response: &PolygonResponse) -> PriceDatum {
-    let results = polygon_response.results.as_ref().unwrap();
-    let stock_entry = &results[results.len() - 1];
+fn format_price_data(polygon_response: &PolygonResponse) -> Vec<PriceDatum> {
     // println!("{:?}", polygon_response); //Debugger statement
-    PriceDatum {
+    let results = match polygon_response.results.as_ref() {
+        Some(results) => results,
+        None => return Vec::new(),
+    };
+
+    results.iter().map(|stock_entry| PriceDatum {
         volume: stock_entry.v as u64,
         high: stock_entry.h,
         low: stock_entry.l,
         close: stock_entry.c,
         open: stock_entry.o,
         timestamp: stock_entry.t,
-    }
-}
\ No newline at end of file
+        vwap: Some(stock_entry.vw),
+        trade_count: Some(stock_entry.n),
+    }).collect()
+}
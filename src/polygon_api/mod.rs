@@ -1,3 +1,4 @@
 pub mod fetch_data;
+pub mod polygon_provider;
 pub mod stock_data_response;
-pub mod stock;
\ No newline at end of file
+pub mod stock;
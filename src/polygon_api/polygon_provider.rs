@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use reqwest::Client;
+
+use crate::{
+    market_data::{
+        fetch_error::FetchError,
+        market_data_provider::{fetch_all_pages, BarsPage, MarketDataProvider, RateLimitHints},
+    },
+    polygon_api::{
+        fetch_data::{aggregates_url, fetch_data, POLYGON_BASE_URL},
+        stock_data_response::PriceDatum,
+    },
+};
+
+/**
+ * Polygon.io aggregates API as a MarketDataProvider
+ */
+pub struct PolygonProvider {
+    api_key: String,
+    base_url: String,
+    client: Client,
+}
+
+impl PolygonProvider {
+    pub fn new(api_key: String) -> Self {
+        Self::with_base_url(api_key, POLYGON_BASE_URL.to_string())
+    }
+
+    /**
+     * Points the provider at another host, used to run against a local mock server
+     */
+    pub fn with_base_url(api_key: String, base_url: String) -> Self {
+        PolygonProvider {
+            api_key,
+            base_url,
+            client: Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl MarketDataProvider for PolygonProvider {
+    fn name(&self) -> &str {
+        "polygon"
+    }
+
+    // Free tier: 5 requests per minute
+    fn rate_limit_hints(&self) -> RateLimitHints {
+        RateLimitHints {
+            requests_per_minute: 5,
+            burst: 5,
+        }
+    }
+
+    async fn fetch_bars(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        timestamp_from: i64,
+        timestamp_to: i64,
+    ) -> Result<Vec<PriceDatum>, FetchError> {
+        fetch_all_pages(self, ticker, timeframe, timestamp_from, timestamp_to).await
+    }
+
+    async fn fetch_bars_page(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        timestamp_from: i64,
+        timestamp_to: i64,
+        page: Option<&str>,
+    ) -> Result<BarsPage, FetchError> {
+        let request_url = match page {
+            Some(next_url) => next_url.to_string(),
+            None => aggregates_url(&self.base_url, ticker, timeframe, timestamp_from, timestamp_to),
+        };
+
+        fetch_data(&self.client, &request_url, &self.api_key).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use axum::{extract::Query, Router};
+
+    use super::*;
+
+    const QQQ_PAGE_1: &str = include_str!("fixtures/aggs_qqq_page_1.json");
+    const QQQ_PAGE_2: &str = include_str!("fixtures/aggs_qqq_page_2.json");
+
+    /**
+     * Serves the first page of a two page window on a random local port, and the second one
+     * to the request carrying the first page's cursor, after checking both carry the key
+     */
+    async fn mock_polygon() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let page_1 = QQQ_PAGE_1.replace("{base_url}", &base_url);
+        let app = Router::new().fallback(move |Query(params): Query<HashMap<String, String>>| async move {
+            assert_eq!(params.get("apiKey").map(String::as_str), Some("test"));
+            match params.get("cursor") {
+                Some(_) => QQQ_PAGE_2.to_string(),
+                None => page_1,
+            }
+        });
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        base_url
+    }
+
+    #[tokio::test]
+    async fn follows_next_url_until_the_window_is_complete() {
+        let provider = PolygonProvider::with_base_url("test".to_string(), mock_polygon().await);
+
+        let bars = provider.fetch_bars("QQQ", 15, 1752499800000, 1752501600000).await.unwrap();
+
+        let timestamps: Vec<u64> = bars.iter().map(|bar| bar.timestamp).collect();
+        assert_eq!(timestamps, vec![1752499800000, 1752500700000, 1752501600000]);
+        assert_eq!(bars[2].close, 553.42);
+        assert_eq!(bars[2].trade_count, Some(35527));
+    }
+
+    #[tokio::test]
+    async fn a_page_hands_back_the_next_url() {
+        let base_url = mock_polygon().await;
+        let provider = PolygonProvider::with_base_url("test".to_string(), base_url.clone());
+
+        let page = provider.fetch_bars_page("QQQ", 15, 1752499800000, 1752501600000, None).await.unwrap();
+        assert_eq!(page.bars.len(), 2);
+        let next_page = page.next_page.unwrap();
+        assert!(next_page.starts_with(&base_url));
+
+        let last_page = provider
+            .fetch_bars_page("QQQ", 15, 1752499800000, 1752501600000, Some(&next_page))
+            .await
+            .unwrap();
+        assert_eq!(last_page.bars.len(), 1);
+        assert_eq!(last_page.next_page, None);
+    }
+}
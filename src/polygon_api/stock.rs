@@ -1,10 +1,17 @@
 use std::collections::{HashMap, VecDeque};
+use chrono::{DateTime, Utc};
 use crate::data_polling::alert_cluster::{AlertCluster, AlertClusterBuilder};
-use crate::data_polling::configs::{MAX_HOUR, MAX_MIN, MIN_HOUR, MIN_MINUTE};
+use crate::config::monitor_config::TickerConfig;
+use crate::database::models::Stock;
+use crate::indicators::{
+  bollinger::BollingerValue,
+  indicator_set::{IndicatorSet, IndicatorState, IndicatorValues},
+  macd::MacdValue,
+};
+use crate::data_polling::constants::MINUTES_TO_MILIS;
+use serde::Serialize;
+use crate::market_calendar::{calendar::{MarketCalendar, TradingSession}, session::Session};
 use crate::polygon_api::stock_data_response::PriceDatum;
-use crate::polygon_api::fetch_data::{NULL_STOCK_DATA_RESPONSE, EMPTY_RESPONSE_DOUBLE_FLAG};
-
-type RawDataTableRow = HashMap<String, f64>;
 
 /**
  * One unit of stock data, any data that belongs to a particular point should be kept here as opposed to on the larger StockData struct
@@ -16,24 +23,51 @@ pub struct StockDatum {
     pub low: f64,
     pub close: f64,
     pub volume: u64,
-    pub ema9: f64,
-    pub ema20: f64,
+    pub indicators: IndicatorValues,
+    pub indicator_state: IndicatorState,
     pub timestamp: u64,
+    pub vwap: Option<f64>,
+    pub trade_count: Option<u32>,
+    pub session: Session,
 }
 
 impl StockDatum {
-  pub fn from_hashmap(dict: &HashMap<String, f64>) -> Self{
-      return StockDatum{
-        open: dict.get("open").copied().unwrap_or(0.0),
-        high: dict.get("high").copied().unwrap_or(0.0),
-        low: dict.get("low").copied().unwrap_or(0.0),
-        close: dict.get("close").copied().unwrap_or(0.0),
-        ema9: dict.get("ema9").copied().unwrap_or(0.0),
-        ema20: dict.get("ema20").copied().unwrap_or(0.0),
-        volume: dict.get("volume").copied().unwrap_or(0.0) as u64,
-        timestamp: dict.get("timestamp").copied().unwrap_or(0.0) as u64,
-      }
-
+  /**
+   * A candle read back from the stocks table, keeping the indicators it was saved with
+   *
+   * @return an error naming the session if the row's session is not one this version knows
+   */
+  pub fn from_stock(stock: &Stock) -> Result<Self, String>{
+      return Ok(StockDatum{
+        open: stock.open,
+        high: stock.high,
+        low: stock.low,
+        close: stock.close,
+        volume: stock.volume as u64,
+        indicators: IndicatorValues {
+          ema_9: stock.ema_9,
+          ema_20: stock.ema_20,
+          rsi_14: stock.rsi_14,
+          macd: match (stock.macd, stock.macd_signal, stock.macd_histogram) {
+            (Some(macd), Some(signal), Some(histogram)) => Some(MacdValue { macd, signal, histogram }),
+            _ => None,
+          },
+          bollinger: match (stock.bollinger_middle, stock.bollinger_upper, stock.bollinger_lower) {
+            (Some(middle), Some(upper), Some(lower)) => Some(BollingerValue { middle, upper, lower }),
+            _ => None,
+          },
+        },
+        indicator_state: IndicatorState {
+          rsi_average_gain: stock.rsi_average_gain,
+          rsi_average_loss: stock.rsi_average_loss,
+          macd_fast_ema: stock.macd_fast_ema,
+          macd_slow_ema: stock.macd_slow_ema,
+        },
+        timestamp: stock.timestamp as u64,
+        vwap: stock.vwap,
+        trade_count: stock.trade_count.map(|trade_count| trade_count as u32),
+        session: stock.session.parse()?,
+      })
   }
 }
 
@@ -44,17 +78,42 @@ impl StockDatum {
  * MIDDAY: The middle of the day after the first 1.5 hrs
  * CLOSE: The last 1 hr
  */
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
 pub enum MarketTimePeriod{
   OPEN,
   MIDDAY,
   CLOSE
 }
 
+impl MarketTimePeriod {
+  /**
+   * Which period of the regular session a candle starting at candle_start belongs to, None outside regular hours
+   *
+   * @param: session: the trading day the candle is in
+   * @param: candle_start: when the candle opened
+   */
+  pub fn for_candle(session: &TradingSession, candle_start: DateTime<Utc>) -> Option<Self> {
+    if !session.contains(candle_start) {
+      return None;
+    }
+    if session.minutes_since_open(candle_start) < 90 {
+      return Some(MarketTimePeriod::OPEN);
+    }
+    if session.minutes_until_close(candle_start) <= 60 {
+      return Some(MarketTimePeriod::CLOSE);
+    }
+    return Some(MarketTimePeriod::MIDDAY);
+  }
+}
+
 /**
  * Represents attributes related to volume
  *  volume groups: a hashmap of 3 vecdeques which track the last 50 volumes per each
  *  volume cache: cached volume values
  */
+// Not filled in yet, the volume spike check works from sum_volume for now
+#[allow(dead_code)]
 pub struct VolumeAttr{ 
   pub volume_groups: HashMap<MarketTimePeriod, VecDeque<i64>>, 
   pub volume_cache: HashMap<MarketTimePeriod, i64>,
@@ -79,87 +138,106 @@ impl VolumeAttr {
  *
  * name, & 'a str: The ticker name
  * stock_data: a piece of stock_data
+ * config, TickerConfig: per ticker settings loaded from the config file
  * daily_resistances, VecDeque<f64>: daily chart resistances, not mutable
  * daily_supports, VecDeque<f64>: daily chart supports, not mutable
  * high_low_queue, VecDeque<StockDatum> a smaller VecDeque  that contains only the points necessary to determine trend
  * current_trend, Trend : The current overall trend of the equity
+ * indicators, IndicatorSet: running indicator state, independent of which candles are still in stock_data
  */
 pub struct StockData<'a> {
     pub name: String,
     pub stock_data: VecDeque<StockDatum>,
-    stock_data_capacity: u32,
+    pub config: TickerConfig,
+    // Trend and volume tracking that is not computed yet
+    #[allow(dead_code)]
     pub daily_resistances: VecDeque<f64>,
+    #[allow(dead_code)]
     pub daily_supports: VecDeque<f64>,
+    #[allow(dead_code)]
     pub high_low_queue: VecDeque<&'a StockDatum>,
+    #[allow(dead_code)]
     pub volume_attrs: VolumeAttr,
-    //Used for standard deviation calculations
-    pub sum_volume: u64
+    pub indicators: IndicatorSet,
+    //Used for standard deviation calculations, kept per session so thin extended hours volume is not mixed with regular hours
+    pub sum_volume: HashMap<Session, u64>,
+    // Timestamp of the newest candle written to the database
+    pub persisted_until: Option<u64>
 }
 
 impl<'a> StockData<'a> {
     /*
      *    Instantiates an empty new struct
      */
-    pub fn new(name: String, stock_data_capacity: u32) -> StockData<'a> {
+    pub fn new(config: TickerConfig) -> StockData<'a> {
         StockData {
-            name,
+            name: config.symbol.clone(),
             stock_data: VecDeque::new(),
-            stock_data_capacity,
+            config,
             daily_resistances: VecDeque::new(),
             daily_supports: VecDeque::new(),
             high_low_queue: VecDeque::new(),
             volume_attrs: VolumeAttr::new(),
-            sum_volume: 0
+            indicators: IndicatorSet::new(),
+            sum_volume: HashMap::new(),
+            persisted_until: None
         }
     }
 
-    pub fn set_stock_data(&mut self, table: &Vec<RawDataTableRow>){
-        let stock_data_points: Vec<StockDatum> =  table.iter().map(|row|{
-          return StockDatum::from_hashmap(row)
-        }).collect();
-        self.stock_data = VecDeque::from(stock_data_points);
+    /**
+     * Refills an empty StockData with candles saved before a restart, so the volume baseline does not start from nothing.
+     * Candles in sessions this ticker no longer polls are dropped, and all of them count as already persisted.
+     *
+     * @param: saved_data: oldest first
+     */
+    pub fn restore_stock_data(&mut self, saved_data: Vec<StockDatum>) {
+      for datum in saved_data {
+        if !self.is_polling_session(datum.session) {
+          continue;
+        }
+        *self.sum_volume.entry(datum.session).or_insert(0) += datum.volume;
+        self.indicators.restore(datum.close, &datum.indicators, &datum.indicator_state);
+        self.stock_data.push_back(datum);
+        self.maybe_evict_if_over_cap();
+      }
+
+      self.persisted_until = self.last_timestamp();
     }
 
     /**
      * Analyzes stock data. 
-     * @maybe_volume: a volume if it was evicted from the queue alongside its stock
+     * @candle_close: when the newest candle closed
      * 
      * @return As a side effect fires off an alert cluster, which tells us if immediately we need t o 
      */
-    pub fn analyze(& self, now_hour: u32, now_min:u32) -> AlertCluster {
+    pub fn analyze(& self, candle_close: DateTime<Utc>) -> AlertCluster {
       
-      let is_time_to_fire_volume_spike =  (now_hour <= MIN_HOUR  && now_min <= MIN_MINUTE) || (now_hour >= MAX_HOUR && now_min >= MAX_MIN);
+      let calendar = self.calendar();
+      // The alert window only narrows an exchange's regular session, markets trading around the clock have no open or close to measure from.
+      // Extended hours candles are only here if the ticker opted in.
+      let is_time_to_fire_volume_spike = match calendar.candle_session(candle_close) {
+        Some(Session::Regular) => match calendar.session_for_candle_close(candle_close) {
+          Some(session) => self.config.alert_window.is_time_to_fire(&session, candle_close),
+          None => true,
+        },
+        Some(_) => self.config.extended_hours,
+        None => false,
+      };
 
       return AlertClusterBuilder::new()
-                .set_is_volume_spike(self.is_volume_spike(3.0))  
+                .set_is_volume_spike(self.is_volume_spike(self.config.volume_spike_k))  
                 .filter_volume_spike(is_time_to_fire_volume_spike)
+                .set_indicators(self.stock_data.back().map(|datum| datum.indicators).unwrap_or_default())
+                .check_rsi_crossing(&self.stock_data.iter().rev().nth(1).map(|datum| datum.indicators).unwrap_or_default())
                 .build();
     }
 
-    fn cache_volume (){
-
-    }
-
-    pub fn calculate_standard_deviation(&mut self){
-      
-
-    }
-
     /**
-     * TODO refactor to return a reference to self.
+     * Appends one candle and updates the indicators and the session's volume sum with it.
+     * Eviction is left to the caller.
      */
-
-    pub fn add_stock_data(
-      &mut self, 
-      stock_data_response: &PriceDatum
-  ) -> (f64, f64, f64, f64) {
-      // In the case of bad responses return 0.0 *4, this is a flag that we shouldn't include the data point 
-      if stock_data_response == &NULL_STOCK_DATA_RESPONSE {
-          return EMPTY_RESPONSE_DOUBLE_FLAG;
-      }
-  
-      let last_close = &stock_data_response.close;
-      let (ema_9, ema_20) = Self::calculate_emas(&self.stock_data, last_close);
+    pub fn add_stock_data(&mut self, stock_data_response: &PriceDatum, session: Session) {
+      let indicators = self.indicators.update(stock_data_response.close);
   
       let incoming_data = StockDatum {
           open: stock_data_response.open,
@@ -167,103 +245,99 @@ impl<'a> StockData<'a> {
           low: stock_data_response.low,
           close: stock_data_response.close,
           volume: stock_data_response.volume,
-          ema9: ema_9,
-          ema20: ema_20,
+          indicators,
+          indicator_state: self.indicators.state(),
           timestamp: stock_data_response.timestamp,
+          vwap: stock_data_response.vwap,
+          trade_count: stock_data_response.trade_count,
+          session,
       };
 
       self.stock_data.push_back(incoming_data);
 
       //Add volume to cached volume
-      self.sum_volume += stock_data_response.volume;
-  
-      
-      return (stock_data_response.high, stock_data_response.low, ema_9, ema_20);
+      *self.sum_volume.entry(session).or_insert(0) += stock_data_response.volume;
   }
 
     /**
-     * Pops from the front of the StockDataVeqDeque if theres 
+     * Adds every candle newer than the last one already held, evicting old candles as it goes.
+     * Candles at or before the last timestamp were already seen in an earlier poll and are skipped,
+     * as are candles outside the sessions this ticker polls.
+     *
+     * @return how many candles were added
      */
-    pub fn pop_front_if_at_capacity(&mut self) -> Option<StockDatum> {
+    pub fn add_new_stock_data(&mut self, price_data: &[PriceDatum]) -> usize {
+      let calendar = self.calendar();
+      let mut added = 0;
 
-      if self.stock_data.len() as u32 > self.stock_data_capacity {
-         return self.stock_data.pop_front();
-      }
-      None
-    }
+      for price_datum in price_data {
+        if self.last_timestamp().is_some_and(|last_timestamp| price_datum.timestamp <= last_timestamp) {
+          continue;
+        }
 
+        let session = match DateTime::from_timestamp_millis(price_datum.timestamp as i64).and_then(|start| calendar.session_at(start)) {
+          Some(session) if self.is_polling_session(session) => session,
+          _ => continue,
+        };
 
-  /**
-   * Encapsulates logic related to retrieving emas,
-   * mostly broken into this separate function in order to keep edge case articulation cleaner.
-   *
-   * Attempts to cover the following edge cases
-   * 1. When we have a stock that we're analyizing that we just added to the the tickers to scan for and there is no length to the stock_data vec, i.e. to prevent underflow
-   *
-   * @param stock_data a veqdeque of stock data.
-   * @param  last_close, a float representing the last close
-   *
-   * @return return value description.
-   */
-  fn calculate_emas(stock_data: &VecDeque<StockDatum>, last_close: &f64) -> (f64, f64) {
-    let mut ema_9: f64;
-    let mut ema_20: f64;
-    let length_stock_data: usize = stock_data.len();
-
-    //Ema 9
-
-    if stock_data.len() < 9 {
-        ema_9 = 0.0;
-    } else {
-        let last_stock_idx = stock_data.len() - 1; // These values might underflow, in these cases we directly return 0.0
-        let last_ema_9 = stock_data[last_stock_idx].ema9;
-        if length_stock_data == 9 {
-            ema_9 = Self::calculate_sma(stock_data, 9.0);
-        }
+        self.add_stock_data(price_datum, session);
+        self.maybe_evict_if_over_cap();
+        added += 1;
+      }
 
-        ema_9 = Self::calculate_ema(9.0, last_close, &last_ema_9);
+      return added;
     }
 
-    //EMA 20
-    if stock_data.len() < 20 {
-        ema_20 = 0.0;
-    } else {
-        let last_stock_idx = stock_data.len() - 1; // These values might underflow, in these cases we directly return 0.0
-        let last_ema_20 = stock_data[last_stock_idx].ema20;
-        if length_stock_data == 20 {
-            ema_20 = Self::calculate_sma(stock_data, 20.0);
-        }
-        ema_20 = Self::calculate_ema(20.0, last_close, &last_ema_20);
+    /**
+     * Candles that have not been written to the database yet, oldest first
+     */
+    pub fn unsaved_stock_data(&self) -> impl Iterator<Item = &StockDatum> {
+      let persisted_until = self.persisted_until;
+      self.stock_data.iter().filter(move |datum| persisted_until.is_none_or(|persisted| datum.timestamp > persisted))
     }
 
-    return (ema_9, ema_20);
-  }
+    /**
+     * Trading hours for the ticker's asset class
+     */
+    pub fn calendar(&self) -> MarketCalendar {
+      return MarketCalendar::for_asset_class(self.config.asset_class);
+    }
 
-  /**
-   * Generically calculate the sma length
-   * TODO: Refactor to use VecDeque.range, and compare performance.
-   */
-  fn calculate_sma(stock_data: &VecDeque<StockDatum>, sma_length: f64) -> f64 {
-    let mut sum: f64 = 0.0;
+    /**
+     * Whether a candle closing at candle_close falls in a session this ticker polls
+     */
+    pub fn is_polling_candle(&self, candle_close: DateTime<Utc>) -> bool {
+      return self.calendar().candle_session(candle_close).is_some_and(|session| self.is_polling_session(session));
+    }
 
-    let length_stock_data: usize = stock_data.len();
-    for idx in 0..length_stock_data {
-        sum += stock_data[length_stock_data - 1 - idx].close;
+    pub fn is_polling_session(&self, session: Session) -> bool {
+      return !session.is_extended() || self.config.extended_hours;
     }
 
-    return sum / sma_length;
-  }
+    pub fn last_timestamp(&self) -> Option<u64> {
+      self.stock_data.back().map(|datum| datum.timestamp)
+    }
 
-  //smoothing: 2.0
-  pub fn calculate_ema(period: f64, close: &f64, prev_ema: &f64) -> f64 {
-    close * (2.0 / (1.0 + period)) + prev_ema * (1.0 - 2.0 / (1.0 + period))
-  }
+    /**
+     * Where the next fetch should start so that any candles missed since the last poll are filled in.
+     * Never reaches back further than the queue can hold.
+     */
+    pub fn fetch_window_start(&self, timestamp_to: i64) -> i64 {
+      let timeframe_millis = self.config.timeframe as i64 * MINUTES_TO_MILIS;
+      let earliest = timestamp_to - self.config.queue_capacity as i64 * timeframe_millis;
 
+      match self.last_timestamp() {
+        Some(last_timestamp) => (last_timestamp as i64).max(earliest),
+        None => timestamp_to - timeframe_millis,
+      }
+    }
 
 
   /**
   * Checks for abnormal spikes in volume. Systematically ignores the first 3 candles, 
   * and last candle of the day as these are typically higher by nature, delegates to other alerts for this
+  *
+  * The newest candle is only compared against candles from the same session.
   */
   fn is_volume_spike(&self, k: f64) -> bool {
 
@@ -274,11 +348,17 @@ impl<'a> StockData<'a> {
         panic!("Value of k should never be over 3");
     }
 
-    let avg = self.sum_volume as f64 / self.stock_data.len() as f64;
+    let session = match self.stock_data.back() {
+        Some(datum) => datum.session,
+        None => return false,
+    };
+
     let volumes: Vec<f64> = self.stock_data.iter()
+                                          .filter(|datum| datum.session == session)
                                           .map(|datum| datum.volume)
                                           .map(|volume| volume as f64)
                                           .collect();
+    let avg = self.sum_volume.get(&session).copied().unwrap_or(0) as f64 / volumes.len() as f64;
 
     let variance = self.get_variance(&volumes, avg);
     let std_dev = variance.sqrt();
@@ -290,11 +370,6 @@ impl<'a> StockData<'a> {
     curr_vol > upper_threshold
   }
 
-  fn get_average(&self, volumes: &[f64]) -> f64 {
-      let sum: f64 = volumes.iter().sum();
-      sum / volumes.len() as f64
-  }
-
   fn get_variance(&self, volumes: &[f64], average: f64) -> f64 {
       let mut res = 0.0;
       let length = volumes.len() as f64;
@@ -312,12 +387,14 @@ impl<'a> StockData<'a> {
    * 1. Removes a price datum from the queue.
    * 2. For the cached sum_volume, decrements it by the volume
    * 
-   * TODO refactor stock_data_capacity to be a usize
+   * TODO refactor queue_capacity to be a usize
    */
   pub fn maybe_evict_if_over_cap(&mut self) -> Option<StockDatum> {
-    if self.stock_data.len() > self.stock_data_capacity as usize {
+    if self.stock_data.len() > self.config.queue_capacity as usize {
       let price_datum =  self.stock_data.pop_front().unwrap();
-      self.sum_volume -= price_datum.volume;
+      if let Some(sum_volume) = self.sum_volume.get_mut(&price_datum.session) {
+        *sum_volume -= price_datum.volume;
+      }
       return Some(price_datum);
     }
 
@@ -325,3 +402,171 @@ impl<'a> StockData<'a> {
   }
 
 }
+
+#[cfg(test)]
+mod tests {
+  use chrono::TimeZone;
+
+  use crate::{
+    database::models::NewStock,
+    test_fixtures::{self, bar, saved_candle},
+  };
+
+  use super::*;
+
+  const MINUTE: i64 = 60 * 1000;
+
+  // Monday 14 July 2025, a regular trading day, in UTC
+  fn at(hour: u32, minute: u32) -> u64 {
+    Utc.with_ymd_and_hms(2025, 7, 14, hour, minute, 0).unwrap().timestamp_millis() as u64
+  }
+
+  fn saved_datum(timestamp: u64, volume: u64, session: Session) -> StockDatum {
+    StockDatum {
+      open: 100.0,
+      high: 100.0,
+      low: 100.0,
+      close: 100.0,
+      volume,
+      indicators: IndicatorValues { ema_9: Some(100.0), ema_20: Some(100.0), ..IndicatorValues::default() },
+      indicator_state: IndicatorState::default(),
+      timestamp,
+      vwap: None,
+      trade_count: None,
+      session,
+    }
+  }
+
+  fn ticker_config(queue_capacity: u32) -> TickerConfig {
+    TickerConfig { queue_capacity, ..test_fixtures::ticker_config("QQQ") }
+  }
+
+  #[test]
+  fn restores_the_newest_saved_candles_of_polled_sessions() {
+    let mut stock_data = StockData::new(ticker_config(2));
+
+    stock_data.restore_stock_data(vec![
+      saved_datum(1, 100, Session::Regular),
+      saved_datum(2, 200, Session::Regular),
+      saved_datum(3, 300, Session::Regular),
+      saved_datum(4, 5_000, Session::AfterHours),
+    ]);
+
+    assert_eq!(stock_data.stock_data.iter().map(|datum| datum.timestamp).collect::<Vec<_>>(), vec![2, 3]);
+    assert_eq!(stock_data.sum_volume[&Session::Regular], 500);
+    assert!(!stock_data.sum_volume.contains_key(&Session::AfterHours));
+    assert_eq!(stock_data.persisted_until, Some(3));
+  }
+
+  #[test]
+  fn emas_are_seeded_from_the_first_closes_and_survive_eviction() {
+    let mut stock_data = StockData::new(ticker_config(2));
+
+    for timestamp in 1..=8 {
+      stock_data.add_stock_data(&bar(timestamp, timestamp as f64), Session::Regular);
+      stock_data.maybe_evict_if_over_cap();
+    }
+    assert_eq!(stock_data.stock_data.back().unwrap().indicators.ema_9, None);
+
+    // The ninth close seeds the EMA with the average of 1 through 9, even though only two candles are held
+    stock_data.add_stock_data(&bar(9, 9.0), Session::Regular);
+    stock_data.maybe_evict_if_over_cap();
+    assert_eq!(stock_data.stock_data.back().unwrap().indicators.ema_9, Some(5.0));
+
+    stock_data.add_stock_data(&bar(10, 10.0), Session::Regular);
+    assert_eq!(stock_data.stock_data.back().unwrap().indicators.ema_9, Some(10.0 * 0.2 + 5.0 * 0.8));
+    assert_eq!(stock_data.stock_data.back().unwrap().indicators.ema_20, None);
+  }
+
+  #[test]
+  fn restored_candles_continue_from_their_saved_emas() {
+    let mut stock_data = StockData::new(ticker_config(50));
+    stock_data.restore_stock_data(vec![saved_datum(1, 100, Session::Regular)]);
+
+    stock_data.add_stock_data(&bar(2, 110.0), Session::Regular);
+
+    let newest = stock_data.stock_data.back().unwrap();
+    assert_eq!(newest.indicators.ema_9, Some(110.0 * 0.2 + 100.0 * 0.8));
+    assert_eq!(newest.indicators.ema_20, Some(110.0 * (2.0 / 21.0) + 100.0 * (1.0 - 2.0 / 21.0)));
+  }
+
+  #[test]
+  fn saved_rows_rebuild_macd_and_bollinger_only_when_complete() {
+    let complete = NewStock {
+      macd: Some(0.5),
+      macd_signal: Some(0.3),
+      macd_histogram: Some(0.2),
+      bollinger_middle: Some(100.0),
+      bollinger_upper: Some(104.0),
+      bollinger_lower: Some(96.0),
+      rsi_average_gain: Some(0.8),
+      macd_slow_ema: Some(99.5),
+      session: "after_hours".to_string(),
+      ..saved_candle(15, 1)
+    };
+    let datum = StockDatum::from_stock(&complete.with_id(1)).unwrap();
+    assert_eq!(datum.indicators.macd, Some(MacdValue { macd: 0.5, signal: 0.3, histogram: 0.2 }));
+    assert_eq!(datum.indicators.bollinger, Some(BollingerValue { middle: 100.0, upper: 104.0, lower: 96.0 }));
+    assert_eq!((datum.indicator_state.rsi_average_gain, datum.indicator_state.macd_slow_ema), (Some(0.8), Some(99.5)));
+    assert_eq!(datum.session, Session::AfterHours);
+
+    let partial = NewStock { macd: Some(0.5), bollinger_upper: Some(104.0), ..saved_candle(15, 2) };
+    let datum = StockDatum::from_stock(&partial.with_id(2)).unwrap();
+    assert_eq!((datum.indicators.macd, datum.indicators.bollinger), (None, None));
+
+    let unknown_session = NewStock { session: "overnight".to_string(), ..saved_candle(15, 3) };
+    assert!(StockDatum::from_stock(&unknown_session.with_id(3)).is_err());
+  }
+
+  #[test]
+  fn overlapping_polls_only_add_candles_not_seen_yet() {
+    let mut stock_data = StockData::new(ticker_config(50));
+
+    assert_eq!(stock_data.add_new_stock_data(&[bar(at(13, 30), 100.0), bar(at(13, 45), 101.0)]), 2);
+    // The next window reaches back to the last candle, which Polygon hands back again
+    assert_eq!(stock_data.add_new_stock_data(&[bar(at(13, 45), 101.5), bar(at(14, 0), 102.0)]), 1);
+    assert_eq!(stock_data.add_new_stock_data(&[bar(at(13, 30), 100.0)]), 0);
+
+    let timestamps: Vec<u64> = stock_data.stock_data.iter().map(|datum| datum.timestamp).collect();
+    assert_eq!(timestamps, vec![at(13, 30), at(13, 45), at(14, 0)]);
+    // The repeated candle kept the close it was first added with
+    assert_eq!(stock_data.stock_data[1].close, 101.0);
+    assert_eq!(stock_data.sum_volume[&Session::Regular], 3_000);
+  }
+
+  #[test]
+  fn new_candles_outside_polled_sessions_are_dropped_and_the_queue_stays_capped() {
+    let mut stock_data = StockData::new(ticker_config(2));
+
+    // 8:00 New York time is pre-market, which this ticker does not poll
+    let added = stock_data.add_new_stock_data(&[
+      bar(at(12, 0), 99.0),
+      bar(at(13, 30), 100.0),
+      bar(at(13, 45), 101.0),
+      bar(at(14, 0), 102.0),
+    ]);
+
+    assert_eq!(added, 3);
+    let timestamps: Vec<u64> = stock_data.stock_data.iter().map(|datum| datum.timestamp).collect();
+    assert_eq!(timestamps, vec![at(13, 45), at(14, 0)]);
+    assert_eq!(stock_data.sum_volume[&Session::Regular], 2_000);
+  }
+
+  #[test]
+  fn fetch_window_starts_at_the_last_candle_but_no_further_back_than_the_queue_holds() {
+    let mut stock_data = StockData::new(ticker_config(4));
+    let timestamp_to = at(15, 0) as i64 - 1;
+
+    // Nothing held yet, only the candle that just closed
+    assert_eq!(stock_data.fetch_window_start(timestamp_to), timestamp_to - 15 * MINUTE);
+
+    // A missed poll reaches back to the last candle held
+    stock_data.add_new_stock_data(&[bar(at(14, 15), 100.0)]);
+    assert_eq!(stock_data.fetch_window_start(timestamp_to), at(14, 15) as i64);
+
+    // After a long gap only as many candles as the queue holds are fetched
+    let mut behind = StockData::new(ticker_config(4));
+    behind.add_new_stock_data(&[bar(at(13, 30), 100.0)]);
+    assert_eq!(behind.fetch_window_start(timestamp_to), timestamp_to - 4 * 15 * MINUTE);
+  }
+}
@@ -2,7 +2,7 @@ use serde::{Serialize, Deserialize};
 
 
 //Response of what we recieve from Polygon.io after we've cleaned and formatted i
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
 pub struct PriceDatum{
     pub high: f64,
     pub low: f64,
@@ -10,6 +10,8 @@ pub struct PriceDatum{
     pub open: f64,
     pub volume: u64,
     pub timestamp: u64,
+    pub vwap: Option<f64>, // volume weighted average price, not every provider reports it
+    pub trade_count: Option<u32>,
 }
 
 
@@ -53,4 +55,5 @@ pub struct PolygonResponse {
     pub status: String,
     pub request_id: String,
     pub count: u32,
+    pub next_url: Option<String>, // only set when there are more results after this page
 }
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::{
+    market_data::{
+        fetch_error::FetchError,
+        market_data_provider::{MarketDataProvider, RateLimitHints},
+    },
+    polygon_api::stock_data_response::PriceDatum,
+};
+
+/**
+ * Serves bars loaded ahead of time, so a replay runs without touching the network
+ *
+ * @bars: each ticker's bars, oldest first
+ */
+pub struct HistoricalProvider {
+    bars: HashMap<String, Vec<PriceDatum>>,
+}
+
+impl HistoricalProvider {
+    pub fn new(bars: HashMap<String, Vec<PriceDatum>>) -> Self {
+        HistoricalProvider { bars }
+    }
+
+    pub fn bar_count(&self, ticker: &str) -> usize {
+        self.bars.get(ticker).map_or(0, Vec::len)
+    }
+}
+
+#[async_trait]
+impl MarketDataProvider for HistoricalProvider {
+    fn name(&self) -> &str {
+        "historical"
+    }
+
+    // Everything is in memory, so there is nothing to protect
+    fn rate_limit_hints(&self) -> RateLimitHints {
+        RateLimitHints {
+            requests_per_minute: u32::MAX,
+            burst: u32::MAX,
+        }
+    }
+
+    async fn fetch_bars(
+        &self,
+        ticker: &str,
+        _timeframe: u32,
+        timestamp_from: i64,
+        timestamp_to: i64,
+    ) -> Result<Vec<PriceDatum>, FetchError> {
+        let bars: Vec<PriceDatum> = self
+            .bars
+            .get(ticker)
+            .map(|bars| {
+                bars.iter()
+                    .filter(|bar| (timestamp_from..=timestamp_to).contains(&(bar.timestamp as i64)))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if bars.is_empty() {
+            return Err(FetchError::EmptyResults);
+        }
+        Ok(bars)
+    }
+}
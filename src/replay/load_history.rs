@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use crate::{
+    cli::HistorySource,
+    config::monitor_config::{ProviderConfig, TickerConfig},
+    database::{
+        connection_pool::connect_database,
+        models::Stock,
+    },
+    market_data::{fetch_error::FetchError, select_provider::select_provider},
+    polygon_api::stock_data_response::PriceDatum,
+    replay::replay_error::ReplayError,
+};
+
+/**
+ * Loads every ticker's bars in the window from the chosen source
+ *
+ * @return bars keyed by ticker, oldest first
+ */
+pub async fn load_history(
+    source: HistorySource,
+    provider_config: &ProviderConfig,
+    tickers: &[TickerConfig],
+    timestamp_from: i64,
+    timestamp_to: i64,
+) -> Result<HashMap<String, Vec<PriceDatum>>, ReplayError> {
+    match source {
+        HistorySource::Provider => load_from_provider(provider_config, tickers, timestamp_from, timestamp_to).await,
+        HistorySource::Database => load_from_database(tickers, timestamp_from, timestamp_to).await,
+    }
+}
+
+/**
+ * Fetches every ticker's bars in the window from the configured provider, one request per ticker
+ *
+ * @return bars keyed by ticker, oldest first, an empty list for tickers with no bars in the window
+ */
+pub async fn load_from_provider(
+    provider_config: &ProviderConfig,
+    tickers: &[TickerConfig],
+    timestamp_from: i64,
+    timestamp_to: i64,
+) -> Result<HashMap<String, Vec<PriceDatum>>, ReplayError> {
+    let provider = select_provider(provider_config)?;
+    let mut history = HashMap::new();
+
+    for ticker in tickers {
+        println!("Loading {} history from {}", ticker.symbol, provider.name());
+        let bars = match provider.fetch_bars(&ticker.symbol, ticker.timeframe, timestamp_from, timestamp_to).await {
+            Ok(bars) => bars,
+            Err(FetchError::EmptyResults) => Vec::new(),
+            Err(source) => return Err(ReplayError::Fetch { ticker: ticker.symbol.clone(), source }),
+        };
+        history.insert(ticker.symbol.clone(), bars);
+    }
+
+    Ok(history)
+}
+
+/**
+ * Reads every ticker's saved candles in the window from the stocks table
+ *
+ * Candles saved before volume was stored all have a volume of 0, which silences every volume alert,
+ * so a ticker whose candles carry no volume at all is called out.
+ *
+ * @return bars keyed by ticker, oldest first
+ */
+pub async fn load_from_database(
+    tickers: &[TickerConfig],
+    timestamp_from: i64,
+    timestamp_to: i64,
+) -> Result<HashMap<String, Vec<PriceDatum>>, ReplayError> {
+    let candles = connect_database().await.map_err(|e| ReplayError::Database(e.to_string()))?.candles;
+    let mut history = HashMap::new();
+
+    for ticker in tickers {
+        let stocks = candles
+            .candles_between(&ticker.symbol, ticker.timeframe, timestamp_from, timestamp_to)
+            .await
+            .map_err(|e| ReplayError::Database(e.to_string()))?;
+        if !stocks.is_empty() && stocks.iter().all(|stock| stock.volume == 0) {
+            eprintln!(
+                "Warning: every saved {} candle in the window has zero volume, volume alerts will not fire. Backfill it again or use --source provider",
+                ticker.symbol
+            );
+        }
+        history.insert(ticker.symbol.clone(), stocks.iter().map(to_price_datum).collect());
+    }
+
+    Ok(history)
+}
+
+fn to_price_datum(stock: &Stock) -> PriceDatum {
+    PriceDatum {
+        open: stock.open,
+        high: stock.high,
+        low: stock.low,
+        close: stock.close,
+        volume: stock.volume as u64,
+        timestamp: stock.timestamp as u64,
+        vwap: stock.vwap,
+        trade_count: stock.trade_count.map(|trade_count| trade_count as u32),
+    }
+}
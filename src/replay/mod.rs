@@ -0,0 +1,5 @@
+pub mod historical_provider;
+pub mod load_history;
+pub mod replay_error;
+pub mod replay_report;
+pub mod run_replay;
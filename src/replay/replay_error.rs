@@ -0,0 +1,52 @@
+use std::{error::Error, fmt};
+
+use crate::market_data::{fetch_error::FetchError, select_provider::ProviderSetupError};
+
+/**
+ * Why a replay or backtest could not run
+ *
+ * InvalidRange: the last day is before the first
+ * InvalidArgument: a command line value is out of range
+ * ProviderSetup: the provider to read history from could not be built
+ * Fetch: the provider failed to return a ticker's history
+ * Database: the stocks table could not be read
+ * Report: the report could not be written
+ */
+#[derive(Debug)]
+pub enum ReplayError {
+    InvalidRange(String),
+    InvalidArgument(String),
+    ProviderSetup(ProviderSetupError),
+    Fetch { ticker: String, source: FetchError },
+    Database(String),
+    Report(String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::InvalidRange(message) => write!(f, "invalid date range: {}", message),
+            ReplayError::InvalidArgument(message) => write!(f, "invalid argument: {}", message),
+            ReplayError::ProviderSetup(e) => write!(f, "{}", e),
+            ReplayError::Fetch { ticker, source } => write!(f, "could not load history for {}: {}", ticker, source),
+            ReplayError::Database(message) => write!(f, "could not read history from the database: {}", message),
+            ReplayError::Report(message) => write!(f, "could not write the report: {}", message),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReplayError::ProviderSetup(e) => Some(e),
+            ReplayError::Fetch { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<ProviderSetupError> for ReplayError {
+    fn from(e: ProviderSetupError) -> Self {
+        ReplayError::ProviderSetup(e)
+    }
+}
@@ -0,0 +1,54 @@
+use std::fmt;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{cli::HistorySource, data_polling::alert_cluster::AlertRecord, market_data::asset_class::display_name};
+
+/**
+ * What a replay did and which alerts it would have sent
+ *
+ * @tickers: one line per replayed ticker
+ * @alerts: every captured alert, in the order they fired
+ */
+#[derive(Debug, Serialize)]
+pub struct ReplayReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub source: HistorySource,
+    pub tickers: Vec<TickerReplay>,
+    pub alerts: Vec<AlertRecord>,
+}
+
+/**
+ * @bars: how many historical bars were available for the ticker
+ * @alerts: how many alerts fired for it
+ */
+#[derive(Debug, Serialize)]
+pub struct TickerReplay {
+    pub ticker: String,
+    pub timeframe: u32,
+    pub bars: usize,
+    pub alerts: usize,
+}
+
+/**
+ * A table for the terminal, the JSON form has every alert
+ */
+impl fmt::Display for ReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Replay {} to {} from {:?}", self.from, self.to, self.source)?;
+        writeln!(f, "{:<12} {:>9} {:>8} {:>7}", "ticker", "timeframe", "bars", "alerts")?;
+        for ticker in &self.tickers {
+            writeln!(
+                f,
+                "{:<12} {:>9} {:>8} {:>7}",
+                display_name(&ticker.ticker),
+                ticker.timeframe,
+                ticker.bars,
+                ticker.alerts
+            )?;
+        }
+        write!(f, "{} alerts in total", self.alerts.len())
+    }
+}
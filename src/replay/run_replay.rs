@@ -0,0 +1,209 @@
+use std::{collections::HashMap, fmt, fs, path::Path, sync::Arc};
+
+use chrono::{DateTime, NaiveDate, TimeDelta, TimeZone, Utc};
+use serde::Serialize;
+use tokio::sync::watch;
+
+use crate::{
+    cli::ReplayArgs,
+    clock::{clock_source::Clock, simulated_clock::SimulatedClock},
+    config::monitor_config::{MonitorSettings, PollingConfig, RawTickerConfig, TickerConfig},
+    market_calendar::calendar::MarketCalendar,
+    data_polling::{alert_cluster::AlertRecord, polling_loop::{monitor_stock_data, PollingContext}},
+    notifier::captured_notifier::CapturedNotifier,
+    polygon_api::{stock::StockData, stock_data_response::PriceDatum},
+    replay::{
+        historical_provider::HistoricalProvider,
+        load_history::load_history,
+        replay_error::ReplayError,
+        replay_report::{ReplayReport, TickerReplay},
+    },
+};
+
+/**
+ * Runs the live polling pipeline over a past date range as fast as it can
+ *
+ * History is loaded up front, then monitor_stock_data runs against it on a simulated clock
+ * that jumps from one candle close to the next. Alerts are captured instead of emailed,
+ * and nothing is written to the database.
+ */
+pub async fn run_replay(settings: &MonitorSettings, args: &ReplayArgs) -> Result<ReplayReport, ReplayError> {
+    if args.to < args.from {
+        return Err(ReplayError::InvalidRange(format!("--to {} is before --from {}", args.to, args.from)));
+    }
+
+    let start = new_york_midnight(args.from);
+    let end = new_york_midnight(args.to.succ_opt().unwrap_or(args.to));
+    let tickers = replay_tickers(settings, &args.tickers);
+
+    // Bars are keyed by start time, the last one in the range starts a candle before end
+    let timestamp_to = end.timestamp_millis() - 1;
+    let history = load_history(args.source, &settings.provider, &tickers, start.timestamp_millis(), timestamp_to).await?;
+
+    let (ticker_replays, alerts) = replay_history(&tickers, history, settings.polling, start, end).await;
+
+    Ok(ReplayReport {
+        from: args.from,
+        to: args.to,
+        source: args.source,
+        tickers: ticker_replays,
+        alerts,
+    })
+}
+
+/**
+ * Feeds history through monitor_stock_data on a simulated clock running from start to end
+ *
+ * @return a summary per ticker, and every alert that fired
+ */
+pub async fn replay_history(
+    tickers: &[TickerConfig],
+    history: HashMap<String, Vec<PriceDatum>>,
+    polling_config: PollingConfig,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> (Vec<TickerReplay>, Vec<AlertRecord>) {
+    let provider = Arc::new(HistoricalProvider::new(history));
+    let clock = Arc::new(SimulatedClock::auto_advancing(start));
+    let notifier = Arc::new(CapturedNotifier::new());
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let (_watchlist_tx, watchlist_rx) = watch::channel(tickers.to_vec());
+    let mut stock_data_map: HashMap<String, StockData> = tickers
+        .iter()
+        .map(|ticker| (ticker.symbol.clone(), StockData::new(ticker.clone())))
+        .collect();
+
+    // The last candle of the range is fetched at end plus the settle delay, stop once the clock moves past it
+    let last_wake = end + TimeDelta::seconds(polling_config.settle_delay_secs as i64);
+    let stop_clock = Arc::clone(&clock);
+    tokio::spawn(async move {
+        stop_clock.wait_until(last_wake + TimeDelta::milliseconds(1)).await;
+        let _ = shutdown_tx.send(true);
+    });
+
+    let context = PollingContext {
+        provider: provider.clone(),
+        polling_config,
+        clock: clock.clone(),
+        notifier: notifier.clone(),
+        database: None,
+    };
+    monitor_stock_data(&mut stock_data_map, watchlist_rx, shutdown_rx, context).await;
+    println!("Replay finished at {}", clock.now());
+
+    let alerts = notifier.alerts();
+    let ticker_replays = tickers
+        .iter()
+        .map(|ticker| TickerReplay {
+            ticker: ticker.symbol.clone(),
+            timeframe: ticker.timeframe,
+            bars: provider.bar_count(&ticker.symbol),
+            alerts: alerts.iter().filter(|alert| alert.ticker == ticker.symbol).count(),
+        })
+        .collect();
+
+    (ticker_replays, alerts)
+}
+
+/**
+ * Prints the summary table, and writes the full report as JSON to path, or prints it when there is no path
+ */
+pub fn write_report<R: Serialize + fmt::Display>(report: &R, path: Option<&Path>) -> Result<(), ReplayError> {
+    println!("{}", report);
+
+    let json = serde_json::to_string_pretty(report).map_err(|e| ReplayError::Report(e.to_string()))?;
+    match path {
+        Some(path) => {
+            fs::write(path, json).map_err(|e| ReplayError::Report(format!("{}: {}", path.display(), e)))?;
+            println!("Report written to {}", path.display());
+        }
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+/**
+ * The requested tickers with their watchlist settings, tickers not on the watchlist use the [defaults]
+ */
+pub fn replay_tickers(settings: &MonitorSettings, requested: &[String]) -> Vec<TickerConfig> {
+    if requested.is_empty() {
+        return settings.tickers.clone();
+    }
+
+    requested
+        .iter()
+        .map(|symbol| {
+            let resolved = RawTickerConfig {
+                symbol: symbol.clone(),
+                timeframe: None,
+                queue_capacity: None,
+                volume_spike_k: None,
+                alert_window: None,
+                extended_hours: None,
+                asset_class: None,
+            }
+            .resolve(&settings.defaults);
+
+            settings
+                .tickers
+                .iter()
+                .find(|ticker| ticker.symbol == resolved.symbol)
+                .cloned()
+                .unwrap_or(resolved)
+        })
+        .collect()
+}
+
+/**
+ * The instant a New York calendar day starts, in the exchange's time zone whatever the host's is
+ */
+pub fn new_york_midnight(date: NaiveDate) -> DateTime<Utc> {
+    MarketCalendar::nyse()
+        .time_zone()
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+        .earliest()
+        .expect("midnight exists in New York")
+        .with_timezone(&Utc)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_fixtures::{bar, ticker_config};
+
+    use super::*;
+
+    const FIFTEEN_MINUTES: u64 = 15 * 60 * 1000;
+
+    // Every regular 15 minute bar of a session opening at open, with the given volume
+    fn session_bars(open: DateTime<Utc>, volume: impl Fn(usize) -> u64) -> Vec<PriceDatum> {
+        (0..26)
+            .map(|idx| PriceDatum {
+                volume: volume(idx),
+                ..bar(open.timestamp_millis() as u64 + idx as u64 * FIFTEEN_MINUTES, 100.5)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn captures_a_spike_on_the_second_day_open() {
+        // Monday and Tuesday 14 and 15 July 2025, opening at 13:30 UTC
+        let monday_open = Utc.with_ymd_and_hms(2025, 7, 14, 13, 30, 0).unwrap();
+        let tuesday_open = Utc.with_ymd_and_hms(2025, 7, 15, 13, 30, 0).unwrap();
+
+        let mut bars = session_bars(monday_open, |_| 1_000);
+        bars.extend(session_bars(tuesday_open, |idx| if idx == 0 { 100_000 } else { 1_000 }));
+        let history = HashMap::from([("QQQ".to_string(), bars)]);
+
+        let start = new_york_midnight(NaiveDate::from_ymd_opt(2025, 7, 14).unwrap());
+        let end = new_york_midnight(NaiveDate::from_ymd_opt(2025, 7, 16).unwrap());
+
+        let (ticker_replays, alerts) = replay_history(&[ticker_config("QQQ")], history, PollingConfig::default(), start, end).await;
+
+        assert_eq!(ticker_replays[0].bars, 52);
+        assert_eq!(ticker_replays[0].alerts, 1);
+        assert_eq!(alerts[0].alert_type, "volume_spike");
+        assert_eq!(alerts[0].candle_timestamp, tuesday_open.timestamp_millis() as u64);
+    }
+}
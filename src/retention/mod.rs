@@ -0,0 +1,2 @@
+pub mod roll_up;
+pub mod run_retention;
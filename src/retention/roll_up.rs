@@ -0,0 +1,177 @@
+use chrono::DateTime;
+use chrono_tz::America::New_York;
+
+use crate::{
+    database::models::{NewStock, Stock},
+    market_calendar::session::Session,
+    replay::run_replay::new_york_midnight,
+};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/**
+ * The start of the candle of a timeframe that a timestamp falls in. Intraday candles start on
+ * multiples of the timeframe since the epoch, the way the providers align them, and daily
+ * candles start at midnight New York time.
+ *
+ * @param timestamp: milliseconds since the epoch
+ * @param timeframe: candle size in minutes, evenly dividing a day
+ */
+pub fn bucket_start(timestamp: i64, timeframe: u32) -> i64 {
+    if timeframe == MINUTES_PER_DAY {
+        let date = DateTime::from_timestamp_millis(timestamp)
+            .expect("timestamp is in range")
+            .with_timezone(&New_York)
+            .date_naive();
+        return new_york_midnight(date).timestamp_millis();
+    }
+
+    let timeframe_ms = timeframe as i64 * 60 * 1000;
+    return timestamp - timestamp.rem_euclid(timeframe_ms);
+}
+
+/**
+ * Combines candles into candles of a coarser timeframe, one per bucket with at least one candle
+ *
+ * Prices, volume, trade count and VWAP are combined exactly. The indicators depend on candles
+ * before the ones given, so they are left empty rather than guessed. A rolled up candle is
+ * in the regular session if any of its candles were.
+ *
+ * @param candles: one ticker's candles of one timeframe, oldest first
+ * @param to_timeframe: candle size in minutes of the rolled up candles
+ * @return the rolled up candles, oldest first
+ */
+pub fn roll_up(candles: &[Stock], to_timeframe: u32) -> Vec<NewStock> {
+    let mut rolled_up: Vec<NewStock> = Vec::new();
+
+    for bucket in candles.chunk_by(|a, b| bucket_start(a.timestamp, to_timeframe) == bucket_start(b.timestamp, to_timeframe)) {
+        rolled_up.push(combine(bucket, to_timeframe));
+    }
+
+    rolled_up
+}
+
+fn combine(bucket: &[Stock], to_timeframe: u32) -> NewStock {
+    let first = &bucket[0];
+    let last = &bucket[bucket.len() - 1];
+    let volume: i64 = bucket.iter().map(|stock| stock.volume).sum();
+
+    let trade_counts: Vec<i32> = bucket.iter().filter_map(|stock| stock.trade_count).collect();
+    let trade_count = if trade_counts.is_empty() { None } else { Some(trade_counts.iter().sum()) };
+
+    // Volume weighted over the candles that have a VWAP, none if no candle has one or none traded
+    let (priced_volume, weighted_price) = bucket
+        .iter()
+        .filter_map(|stock| stock.vwap.map(|vwap| (stock.volume, vwap * stock.volume as f64)))
+        .fold((0_i64, 0.0), |(volume, price), (v, p)| (volume + v, price + p));
+    let vwap = if priced_volume > 0 { Some(weighted_price / priced_volume as f64) } else { None };
+
+    let regular = Session::Regular.as_str();
+    let session = if bucket.iter().any(|stock| stock.session == regular) {
+        regular.to_string()
+    } else {
+        first.session.clone()
+    };
+
+    NewStock {
+        ticker: first.ticker.clone(),
+        open: first.open,
+        close: last.close,
+        high: bucket.iter().map(|stock| stock.high).fold(f64::MIN, f64::max),
+        low: bucket.iter().map(|stock| stock.low).fold(f64::MAX, f64::min),
+        ema_9: None,
+        timestamp: bucket_start(first.timestamp, to_timeframe),
+        timeframe: to_timeframe as i32,
+        volume,
+        ema_20: None,
+        vwap,
+        trade_count,
+        session,
+        rsi_14: None,
+        macd: None,
+        macd_signal: None,
+        macd_histogram: None,
+        bollinger_middle: None,
+        bollinger_upper: None,
+        bollinger_lower: None,
+        rsi_average_gain: None,
+        rsi_average_loss: None,
+        macd_fast_ema: None,
+        macd_slow_ema: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::test_fixtures::saved_candle;
+
+    use super::*;
+
+    const MINUTE: i64 = 60 * 1000;
+
+    fn candle(timestamp: i64, open: f64, close: f64, volume: i64, vwap: Option<f64>, session: &str) -> Stock {
+        NewStock {
+            open,
+            close,
+            high: open.max(close) + 1.0,
+            low: open.min(close) - 1.0,
+            timeframe: 1,
+            volume,
+            vwap,
+            trade_count: Some(10),
+            session: session.to_string(),
+            ..saved_candle(1, timestamp)
+        }
+        .with_id(0)
+    }
+
+    #[test]
+    fn intraday_buckets_start_on_multiples_of_the_timeframe() {
+        let start = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap().and_hms_opt(13, 30, 0).unwrap().and_utc().timestamp_millis();
+
+        assert_eq!(bucket_start(start, 15), start);
+        assert_eq!(bucket_start(start + 14 * MINUTE, 15), start);
+        assert_eq!(bucket_start(start + 15 * MINUTE, 15), start + 15 * MINUTE);
+        assert_eq!(bucket_start(start + 29 * MINUTE, 60), start - 30 * MINUTE);
+    }
+
+    #[test]
+    fn daily_buckets_start_at_new_york_midnight() {
+        let midnight = new_york_midnight(NaiveDate::from_ymd_opt(2025, 7, 14).unwrap()).timestamp_millis();
+
+        assert_eq!(bucket_start(midnight, 1440), midnight);
+        // 23:59 New York time is already the next day in UTC
+        assert_eq!(bucket_start(midnight + 1439 * MINUTE, 1440), midnight);
+        assert_eq!(bucket_start(midnight - MINUTE, 1440), midnight - 1440 * MINUTE);
+    }
+
+    #[test]
+    fn combines_each_bucket_into_one_candle() {
+        let start = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap().and_hms_opt(13, 0, 0).unwrap().and_utc().timestamp_millis();
+        let candles = vec![
+            candle(start, 10.0, 11.0, 100, Some(10.5), "pre_market"),
+            candle(start + 5 * MINUTE, 11.0, 9.0, 300, Some(10.0), "regular"),
+            candle(start + 14 * MINUTE, 9.0, 12.0, 0, None, "regular"),
+            candle(start + 15 * MINUTE, 12.0, 13.0, 50, None, "regular"),
+        ];
+
+        let rolled_up = roll_up(&candles, 15);
+
+        assert_eq!(rolled_up.len(), 2);
+        let first = &rolled_up[0];
+        assert_eq!(first.timestamp, start);
+        assert_eq!(first.timeframe, 15);
+        assert_eq!((first.open, first.close, first.high, first.low), (10.0, 12.0, 13.0, 8.0));
+        assert_eq!(first.volume, 400);
+        assert_eq!(first.trade_count, Some(30));
+        assert_eq!(first.vwap, Some((10.5 * 100.0 + 10.0 * 300.0) / 400.0));
+        assert_eq!(first.session, "regular");
+        assert_eq!((first.ema_9, first.ema_20), (None, None));
+
+        let second = &rolled_up[1];
+        assert_eq!(second.timestamp, start + 15 * MINUTE);
+        assert_eq!(second.vwap, None);
+    }
+}
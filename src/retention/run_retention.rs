@@ -0,0 +1,197 @@
+use std::{collections::HashSet, sync::Arc};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use tokio::sync::watch;
+
+use crate::{
+    clock::clock_source::Clock,
+    config::monitor_config::{RetentionConfig, RetentionPolicy},
+    database::{candle_repository::CandleRepository, database_error::DatabaseError, models::NewStock},
+    retention::roll_up::{bucket_start, roll_up},
+};
+
+// Candles are read this many days at a time while rolling up, so the first run over a large table stays small in memory
+const ROLL_UP_WINDOW_DAYS: i64 = 30;
+
+/**
+ * Applies the retention policies now and then every interval_hours until shutdown_rx flips to true.
+ * Does nothing without policies.
+ */
+pub async fn run_retention(
+    retention: RetentionConfig,
+    candles: Arc<dyn CandleRepository>,
+    clock: Arc<dyn Clock>,
+    mut shutdown_rx: watch::Receiver<bool>,
+) {
+    if retention.policies.is_empty() {
+        return;
+    }
+
+    let interval = TimeDelta::hours(retention.interval_hours as i64);
+    loop {
+        apply_retention(&retention, candles.as_ref(), clock.now()).await;
+
+        tokio::select! {
+            _ = clock.sleep_until(clock.now() + interval) => {}
+            _ = shutdown_rx.changed() => return,
+        }
+    }
+}
+
+/**
+ * Runs every policy once, finer timeframes first so their rolled up candles are in place
+ * before the coarser timeframe's own policy looks at them. A ticker that fails is logged
+ * and picked up again on the next run.
+ */
+pub async fn apply_retention(retention: &RetentionConfig, candles: &dyn CandleRepository, now: DateTime<Utc>) {
+    let mut policies = retention.policies.clone();
+    policies.sort_by_key(|policy| policy.timeframe);
+
+    for policy in &policies {
+        let tickers = match candles.oldest_candles(policy.timeframe).await {
+            Ok(tickers) => tickers,
+            Err(e) => {
+                eprintln!("Retention for {} minute candles skipped: {}", policy.timeframe, e);
+                continue;
+            }
+        };
+
+        for (ticker, oldest) in tickers {
+            if let Err(e) = apply_policy(policy, candles, &ticker, oldest, now).await {
+                eprintln!("{}: retention for {} minute candles failed, retrying next run: {}", ticker, policy.timeframe, e);
+            }
+        }
+    }
+}
+
+/**
+ * Rolls up and deletes one ticker's candles older than the policy allows. The cutoff is moved back
+ * to the start of a rolled up candle, so a rolled up candle is only ever built from all of its candles.
+ *
+ * @param oldest: timestamp of the ticker's oldest candle of the policy's timeframe
+ */
+async fn apply_policy(
+    policy: &RetentionPolicy,
+    candles: &dyn CandleRepository,
+    ticker: &str,
+    oldest: i64,
+    now: DateTime<Utc>,
+) -> Result<(), DatabaseError> {
+    let keep_from = (now - TimeDelta::days(policy.keep_days as i64)).timestamp_millis();
+    let cutoff = bucket_start(keep_from, policy.roll_up_to.unwrap_or(policy.timeframe));
+    if oldest >= cutoff {
+        return Ok(());
+    }
+
+    let mut rolled_up = 0;
+    if let Some(roll_up_to) = policy.roll_up_to {
+        rolled_up = roll_up_before(candles, ticker, policy.timeframe, roll_up_to, oldest, cutoff).await?;
+    }
+
+    // Only deleted once every rolled up candle is saved, a failure above leaves the candles for the next run
+    let deleted = candles.delete_candles_before(ticker, policy.timeframe, cutoff).await?;
+    println!(
+        "{}: deleted {} {} minute candles older than {} days, saved {} rolled up candles",
+        ticker, deleted, policy.timeframe, policy.keep_days, rolled_up
+    );
+
+    Ok(())
+}
+
+/**
+ * Saves rolled up candles for every bucket between the oldest candle and the cutoff. Buckets
+ * already saved in the coarser timeframe, e.g. because it is polled as well, are left as they are.
+ *
+ * @return how many rolled up candles were saved
+ */
+async fn roll_up_before(
+    candles: &dyn CandleRepository,
+    ticker: &str,
+    timeframe: u32,
+    roll_up_to: u32,
+    oldest: i64,
+    cutoff: i64,
+) -> Result<usize, DatabaseError> {
+    let window = TimeDelta::days(ROLL_UP_WINDOW_DAYS).num_milliseconds();
+    let mut saved = 0;
+
+    let mut window_start = bucket_start(oldest, roll_up_to);
+    while window_start < cutoff {
+        let window_end = bucket_start(window_start + window, roll_up_to).min(cutoff);
+
+        let source = candles.candles_between(ticker, timeframe, window_start, window_end - 1).await?;
+        let existing: HashSet<i64> = candles
+            .candles_between(ticker, roll_up_to, window_start, window_end - 1)
+            .await?
+            .iter()
+            .map(|stock| stock.timestamp)
+            .collect();
+        let missing: Vec<NewStock> = roll_up(&source, roll_up_to)
+            .into_iter()
+            .filter(|candle| !existing.contains(&candle.timestamp))
+            .collect();
+
+        if !missing.is_empty() {
+            saved += candles.save_candles(missing).await?;
+        }
+        window_start = window_end;
+    }
+
+    Ok(saved)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use crate::{database::in_memory_repository::InMemoryRepository, test_fixtures::saved_candle};
+
+    use super::*;
+
+    const MINUTE: i64 = 60 * 1000;
+
+    fn one_minute_candle(timestamp: i64) -> NewStock {
+        NewStock {
+            open: 10.0,
+            close: 11.0,
+            high: 12.0,
+            low: 9.0,
+            volume: 100,
+            vwap: Some(10.5),
+            trade_count: Some(5),
+            ..saved_candle(1, timestamp)
+        }
+    }
+
+    #[tokio::test]
+    async fn rolls_up_and_deletes_candles_older_than_the_policy() {
+        let candles = InMemoryRepository::new();
+        let start = Utc.with_ymd_and_hms(2025, 7, 14, 13, 30, 0).unwrap();
+        let rows: Vec<NewStock> = (0..3 * 24 * 60).map(|minute| one_minute_candle(start.timestamp_millis() + minute * MINUTE)).collect();
+        candles.save_candles(rows).await.unwrap();
+
+        let retention = RetentionConfig {
+            interval_hours: 24,
+            policies: vec![RetentionPolicy { timeframe: 1, keep_days: 1, roll_up_to: Some(15) }],
+        };
+        // Keeps from 13:37 on the 16th, which is moved back to 13:30 so the 13:30 candle stays whole
+        let now = start + TimeDelta::days(3) + TimeDelta::minutes(7);
+        apply_retention(&retention, &candles, now).await;
+
+        let cutoff = (start + TimeDelta::days(2)).timestamp_millis();
+        let kept = candles.candles_between("QQQ", 1, i64::MIN, i64::MAX).await.unwrap();
+        assert_eq!(kept.first().unwrap().timestamp, cutoff);
+        assert_eq!(kept.len(), 24 * 60);
+
+        let rolled_up = candles.candles_between("QQQ", 15, i64::MIN, i64::MAX).await.unwrap();
+        assert_eq!(rolled_up.len(), 2 * 24 * 4);
+        assert_eq!(rolled_up.first().unwrap().timestamp, start.timestamp_millis());
+        assert_eq!(rolled_up.last().unwrap().timestamp, cutoff - 15 * MINUTE);
+        assert!(rolled_up.iter().all(|candle| candle.volume == 1500 && candle.trade_count == Some(75)));
+
+        // Running again finds nothing left to do
+        apply_retention(&retention, &candles, now).await;
+        assert_eq!(candles.candles_between("QQQ", 15, i64::MIN, i64::MAX).await.unwrap().len(), 2 * 24 * 4);
+        assert_eq!(candles.candles_between("QQQ", 1, i64::MIN, i64::MAX).await.unwrap().len(), 24 * 60);
+    }
+}
@@ -0,0 +1,19 @@
+use std::{path::PathBuf, sync::Arc};
+
+use tokio::sync::watch;
+
+use crate::{config::monitor_config::TickerConfig, database::repositories::Repositories};
+
+/**
+ * State shared by the axum handlers
+ *
+ * @config_path: the config file the watchlist is reloaded from
+ * @watchlist_tx: publishes a new watchlist to the polling loop
+ * @database: where candles and alerts are read from, None when running without a database
+ */
+#[derive(Clone)]
+pub struct AppState {
+    pub config_path: Arc<PathBuf>,
+    pub watchlist_tx: Arc<watch::Sender<Vec<TickerConfig>>>,
+    pub database: Option<Repositories>,
+}
@@ -0,0 +1,51 @@
+use axum::{extract::State, http::StatusCode};
+
+use crate::routes::app_state::AppState;
+
+/**
+ * GET /health
+ *
+ * The server is up if it answers at all, the body says whether the database does too.
+ *
+ * @return 503 when the database is configured but cannot be reached
+ */
+pub async fn health(State(app_state): State<AppState>) -> (StatusCode, String) {
+    let repositories = match &app_state.database {
+        Some(repositories) => repositories,
+        None => return (StatusCode::OK, "ok, database disabled".to_string()),
+    };
+
+    match repositories.candles.check_health().await {
+        Ok(()) => (StatusCode::OK, "ok".to_string()),
+        Err(e) => (StatusCode::SERVICE_UNAVAILABLE, e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        database::{in_memory_repository::InMemoryRepository, repositories::Repositories},
+        test_fixtures::app_state,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_a_configured_database_that_is_down_as_unavailable() {
+        let (status, body) = health(State(app_state(None))).await;
+        assert_eq!((status, body.as_str()), (StatusCode::OK, "ok, database disabled"));
+
+        let repository = Arc::new(InMemoryRepository::new());
+        let app_state = app_state(Some(Repositories { candles: repository.clone(), alerts: repository.clone() }));
+        assert_eq!(health(State(app_state.clone())).await.0, StatusCode::OK);
+
+        repository.set_unreachable(true);
+        assert_eq!(health(State(app_state.clone())).await.0, StatusCode::SERVICE_UNAVAILABLE);
+
+        // The same repositories answer again once the database is back
+        repository.set_unreachable(false);
+        assert_eq!(health(State(app_state)).await, (StatusCode::OK, "ok".to_string()));
+    }
+}
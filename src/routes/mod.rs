@@ -0,0 +1,5 @@
+pub mod app_state;
+pub mod health;
+pub mod recent_alerts;
+pub mod reload_watchlist;
+pub mod ticker_candles;
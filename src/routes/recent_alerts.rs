@@ -0,0 +1,96 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::{
+    database::models::Alert,
+    routes::{
+        app_state::AppState,
+        ticker_candles::{DEFAULT_LIMIT, MAX_LIMIT},
+    },
+};
+
+/**
+ * @ticker: only this ticker's alerts, every ticker's when missing
+ * @limit: how many alerts, 100 by default and 1000 at most
+ */
+#[derive(Debug, Deserialize)]
+pub struct AlertsQuery {
+    pub ticker: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/**
+ * GET /alerts
+ *
+ * @return the most recently fired alerts, newest first
+ */
+pub async fn recent_alerts(
+    State(app_state): State<AppState>,
+    Query(query): Query<AlertsQuery>,
+) -> Result<Json<Vec<Alert>>, (StatusCode, String)> {
+    let repositories = app_state
+        .database
+        .as_ref()
+        .ok_or((StatusCode::SERVICE_UNAVAILABLE, "running without a database".to_string()))?;
+
+    let ticker = query.ticker.map(|ticker| ticker.to_uppercase());
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let alerts = repositories
+        .alerts
+        .recent_alerts(ticker.as_deref(), limit)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(alerts))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        database::{models::NewAlert, repositories::Repositories},
+        test_fixtures::{app_state, fired_alert},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn returns_the_newest_alerts_for_one_ticker_or_all() {
+        let repositories = Repositories::in_memory();
+        repositories
+            .alerts
+            .save_alerts(vec![
+                fired_alert("QQQ", 1_000_000),
+                fired_alert("NVDA", 2_000_000),
+                NewAlert { rsi_14: Some(71.0), ..fired_alert("QQQ", 3_000_000) },
+            ])
+            .await
+            .unwrap();
+        let app_state = app_state(Some(repositories));
+
+        let Json(all) = recent_alerts(State(app_state.clone()), Query(AlertsQuery { ticker: None, limit: Some(2) }))
+            .await
+            .unwrap();
+        let newest: Vec<(&str, i64)> = all.iter().map(|alert| (alert.ticker.as_str(), alert.fired_at)).collect();
+        assert_eq!(newest, vec![("QQQ", 3_000_000), ("NVDA", 2_000_000)]);
+
+        let Json(qqq) = recent_alerts(State(app_state), Query(AlertsQuery { ticker: Some("qqq".to_string()), limit: None }))
+            .await
+            .unwrap();
+        assert_eq!(qqq.iter().map(|alert| alert.fired_at).collect::<Vec<_>>(), vec![3_000_000, 1_000_000]);
+
+        // The fired candle's indicators are part of the response
+        let body = serde_json::to_value(&qqq[0]).unwrap();
+        assert_eq!(body["rsi_14"], 71.0);
+        assert!(body["macd"].is_null());
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_databases() {
+        let no_database = recent_alerts(State(app_state(None)), Query(AlertsQuery { ticker: None, limit: None })).await;
+        assert_eq!(no_database.unwrap_err().0, StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
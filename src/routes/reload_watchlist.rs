@@ -0,0 +1,27 @@
+use axum::{extract::State, http::StatusCode};
+
+use crate::{config::load_config::load_config, data_polling::watchlist_diff::WatchlistDiff, routes::app_state::AppState};
+
+/**
+ * POST /watchlist/reload
+ *
+ * Re-reads the config file and hands the new watchlist to the polling loop,
+ * which picks it up before its next fetch. An invalid file leaves the running watchlist untouched.
+ * Only the [[tickers]] are reloaded, changing the provider still needs a restart.
+ *
+ * @return the diff between the running and the reloaded watchlist
+ */
+pub async fn reload_watchlist(State(app_state): State<AppState>) -> (StatusCode, String) {
+    let tickers = match load_config(&app_state.config_path) {
+        Ok(settings) => settings.tickers,
+        Err(e) => {
+            eprintln!("Rejected watchlist reload: {}", e);
+            return (StatusCode::UNPROCESSABLE_ENTITY, e.to_string());
+        }
+    };
+
+    let diff = WatchlistDiff::between(&app_state.watchlist_tx.borrow(), &tickers);
+    app_state.watchlist_tx.send_replace(tickers);
+
+    (StatusCode::OK, diff.to_string())
+}
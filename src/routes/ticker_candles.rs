@@ -0,0 +1,135 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::{database::models::Stock, market_calendar::session::Session, routes::app_state::AppState};
+
+pub const DEFAULT_LIMIT: usize = 100;
+pub const MAX_LIMIT: usize = 1_000;
+
+/**
+ * @timeframe: candle length in minutes, defaults to the ticker's timeframe on the watchlist
+ * @limit: how many of the newest candles, 100 by default and 1000 at most
+ */
+#[derive(Debug, Deserialize)]
+pub struct CandlesQuery {
+    pub timeframe: Option<u32>,
+    pub limit: Option<usize>,
+}
+
+/**
+ * GET /tickers/{ticker}/candles
+ *
+ * @return the ticker's newest saved candles, oldest first
+ */
+pub async fn ticker_candles(
+    State(app_state): State<AppState>,
+    Path(ticker): Path<String>,
+    Query(query): Query<CandlesQuery>,
+) -> Result<Json<Vec<Stock>>, (StatusCode, String)> {
+    let repositories = app_state
+        .database
+        .as_ref()
+        .ok_or((StatusCode::SERVICE_UNAVAILABLE, "running without a database".to_string()))?;
+
+    let ticker = ticker.to_uppercase();
+    let watchlist_timeframe = app_state
+        .watchlist_tx
+        .borrow()
+        .iter()
+        .find(|ticker_config| ticker_config.symbol == ticker)
+        .map(|ticker_config| ticker_config.timeframe);
+    let timeframe = query.timeframe.or(watchlist_timeframe).ok_or((
+        StatusCode::NOT_FOUND,
+        format!("{} is not on the watchlist, pass ?timeframe= to read its saved candles", ticker),
+    ))?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let candles = repositories
+        .candles
+        .latest_candles(&ticker, timeframe, &Session::ALL, limit)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(candles))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        database::{models::NewStock, repositories::Repositories},
+        test_fixtures::{app_state, saved_candle},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn returns_the_newest_candles_at_the_watchlist_timeframe() {
+        let repositories = Repositories::in_memory();
+        repositories.candles.save_candles((1..=4).map(|timestamp| saved_candle(15, timestamp)).collect()).await.unwrap();
+
+        let Json(candles) = ticker_candles(
+            State(app_state(Some(repositories))),
+            Path("qqq".to_string()),
+            Query(CandlesQuery { timeframe: None, limit: Some(2) }),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(candles.iter().map(|candle| candle.timestamp).collect::<Vec<_>>(), vec![3, 4]);
+    }
+
+    #[tokio::test]
+    async fn candles_carry_their_indicators_but_not_the_running_state() {
+        let repositories = Repositories::in_memory();
+        let candle = NewStock {
+            rsi_14: Some(64.0),
+            macd: Some(0.5),
+            macd_signal: Some(0.3),
+            macd_histogram: Some(0.2),
+            bollinger_middle: Some(100.0),
+            bollinger_upper: Some(104.0),
+            bollinger_lower: Some(96.0),
+            rsi_average_gain: Some(0.8),
+            macd_fast_ema: Some(100.5),
+            ..saved_candle(15, 1)
+        };
+        repositories.candles.save_candles(vec![candle]).await.unwrap();
+
+        let Json(candles) = ticker_candles(
+            State(app_state(Some(repositories))),
+            Path("QQQ".to_string()),
+            Query(CandlesQuery { timeframe: None, limit: None }),
+        )
+        .await
+        .unwrap();
+
+        let body = serde_json::to_value(&candles[0]).unwrap();
+        assert_eq!((body["ema_9"].as_f64(), body["rsi_14"].as_f64()), (Some(100.0), Some(64.0)));
+        assert_eq!((body["macd"].as_f64(), body["macd_signal"].as_f64(), body["macd_histogram"].as_f64()), (Some(0.5), Some(0.3), Some(0.2)));
+        assert_eq!(body["bollinger_upper"].as_f64(), Some(104.0));
+        assert!(body.get("rsi_average_gain").is_none() && body.get("macd_fast_ema").is_none());
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_tickers_and_missing_databases() {
+        let unknown = ticker_candles(
+            State(app_state(Some(Repositories::in_memory()))),
+            Path("SPY".to_string()),
+            Query(CandlesQuery { timeframe: None, limit: None }),
+        )
+        .await;
+        assert_eq!(unknown.unwrap_err().0, StatusCode::NOT_FOUND);
+
+        let no_database = ticker_candles(
+            State(app_state(None)),
+            Path("QQQ".to_string()),
+            Query(CandlesQuery { timeframe: None, limit: None }),
+        )
+        .await;
+        assert_eq!(no_database.unwrap_err().0, StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -1,5 +1,25 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    alerts (id) {
+        id -> Int4,
+        ticker -> Varchar,
+        alert_type -> Varchar,
+        candle_timestamp -> Int8,
+        fired_at -> Int8,
+        created_at -> Nullable<Timestamptz>,
+        ema_9 -> Nullable<Float8>,
+        ema_20 -> Nullable<Float8>,
+        rsi_14 -> Nullable<Float8>,
+        macd -> Nullable<Float8>,
+        macd_signal -> Nullable<Float8>,
+        macd_histogram -> Nullable<Float8>,
+        bollinger_middle -> Nullable<Float8>,
+        bollinger_upper -> Nullable<Float8>,
+        bollinger_lower -> Nullable<Float8>,
+    }
+}
+
 diesel::table! {
     stocks (id) {
         id -> Int4,
@@ -8,8 +28,30 @@ diesel::table! {
         close -> Float8,
         high -> Float8,
         low -> Float8,
-        ema_9 -> Float8,
+        ema_9 -> Nullable<Float8>,
         timestamp -> Int8,
         created_at -> Nullable<Timestamptz>,
+        timeframe -> Int4,
+        volume -> Int8,
+        ema_20 -> Nullable<Float8>,
+        vwap -> Nullable<Float8>,
+        trade_count -> Nullable<Int4>,
+        session -> Varchar,
+        rsi_14 -> Nullable<Float8>,
+        macd -> Nullable<Float8>,
+        macd_signal -> Nullable<Float8>,
+        macd_histogram -> Nullable<Float8>,
+        bollinger_middle -> Nullable<Float8>,
+        bollinger_upper -> Nullable<Float8>,
+        bollinger_lower -> Nullable<Float8>,
+        rsi_average_gain -> Nullable<Float8>,
+        rsi_average_loss -> Nullable<Float8>,
+        macd_fast_ema -> Nullable<Float8>,
+        macd_slow_ema -> Nullable<Float8>,
     }
 }
+
+diesel::allow_tables_to_appear_in_same_query!(
+    alerts,
+    stocks,
+);
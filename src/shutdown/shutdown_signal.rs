@@ -0,0 +1,26 @@
+use tokio::signal;
+
+/**
+ * Resolves on the first Ctrl-C or SIGTERM
+ */
+pub async fn wait_for_shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c().await.expect("failed to listen for Ctrl-C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => println!("Received Ctrl-C"),
+        _ = terminate => println!("Received SIGTERM"),
+    }
+}
@@ -0,0 +1,147 @@
+/*
+ * Builders for the values unit tests keep needing. Tests override what they care about with
+ * struct update syntax, e.g. TickerConfig { queue_capacity: 2, ..ticker_config("QQQ") }
+ */
+use std::{path::PathBuf, sync::Arc};
+
+use tokio::sync::watch;
+
+use crate::{
+    config::monitor_config::{AlertWindow, TickerConfig},
+    database::{
+        models::{NewAlert, NewStock},
+        repositories::Repositories,
+    },
+    indicators::indicator_set::IndicatorValues,
+    market_data::asset_class::AssetClass,
+    polygon_api::stock_data_response::PriceDatum,
+    routes::app_state::AppState,
+};
+
+/**
+ * A watchlist entry with the config file's defaults: 15 minute candles, 50 kept, regular hours only
+ */
+pub fn ticker_config(symbol: &str) -> TickerConfig {
+    TickerConfig {
+        symbol: symbol.to_string(),
+        timeframe: 15,
+        queue_capacity: 50,
+        volume_spike_k: 3.0,
+        alert_window: AlertWindow::default(),
+        extended_hours: false,
+        asset_class: AssetClass::infer(symbol),
+    }
+}
+
+/**
+ * A flat bar at close with 1000 shares traded
+ */
+pub fn bar(timestamp: u64, close: f64) -> PriceDatum {
+    PriceDatum {
+        open: close,
+        high: close,
+        low: close,
+        close,
+        volume: 1_000,
+        timestamp,
+        vwap: None,
+        trade_count: None,
+    }
+}
+
+/**
+ * Closes that rise and fall by different amounts, so every indicator has something to measure
+ */
+pub fn wavy_closes(count: usize) -> Vec<f64> {
+    (0..count).map(|i| 100.0 + (i as f64 * 0.7).sin() * 5.0 + i as f64 * 0.1).collect()
+}
+
+/**
+ * Asserts two sets of indicators match up to rounding, e.g. a restored Bollinger window summed in a different order
+ */
+pub fn assert_indicators_close(actual: &IndicatorValues, expected: &IndicatorValues) {
+    let flatten = |values: &IndicatorValues| {
+        [
+            values.ema_9,
+            values.ema_20,
+            values.rsi_14,
+            values.macd.map(|macd| macd.macd),
+            values.macd.map(|macd| macd.signal),
+            values.macd.map(|macd| macd.histogram),
+            values.bollinger.map(|bollinger| bollinger.middle),
+            values.bollinger.map(|bollinger| bollinger.upper),
+            values.bollinger.map(|bollinger| bollinger.lower),
+        ]
+    };
+
+    for (actual_value, expected_value) in flatten(actual).into_iter().zip(flatten(expected)) {
+        match (actual_value, expected_value) {
+            (Some(a), Some(e)) => assert!((a - e).abs() < 1e-9, "expected {:?}, got {:?}", expected, actual),
+            _ => assert_eq!(actual_value, expected_value, "expected {:?}, got {:?}", expected, actual),
+        }
+    }
+}
+
+/**
+ * A flat regular session QQQ row at 100 with 1000 shares traded, EMAs of 100 and the other indicators still warming up
+ */
+pub fn saved_candle(timeframe: i32, timestamp: i64) -> NewStock {
+    NewStock {
+        ticker: "QQQ".to_string(),
+        open: 100.0,
+        close: 100.0,
+        high: 100.0,
+        low: 100.0,
+        ema_9: Some(100.0),
+        timestamp,
+        timeframe,
+        volume: 1_000,
+        ema_20: Some(100.0),
+        vwap: None,
+        trade_count: None,
+        session: "regular".to_string(),
+        rsi_14: None,
+        macd: None,
+        macd_signal: None,
+        macd_histogram: None,
+        bollinger_middle: None,
+        bollinger_upper: None,
+        bollinger_lower: None,
+        rsi_average_gain: None,
+        rsi_average_loss: None,
+        macd_fast_ema: None,
+        macd_slow_ema: None,
+    }
+}
+
+/**
+ * A volume spike alert fired for a candle one minute before fired_at, with no indicators
+ */
+pub fn fired_alert(ticker: &str, fired_at: i64) -> NewAlert {
+    NewAlert {
+        ticker: ticker.to_string(),
+        alert_type: "volume_spike".to_string(),
+        candle_timestamp: fired_at - 60_000,
+        fired_at,
+        ema_9: None,
+        ema_20: None,
+        rsi_14: None,
+        macd: None,
+        macd_signal: None,
+        macd_histogram: None,
+        bollinger_middle: None,
+        bollinger_upper: None,
+        bollinger_lower: None,
+    }
+}
+
+/**
+ * Handler state watching QQQ at the config file's defaults
+ */
+pub fn app_state(database: Option<Repositories>) -> AppState {
+    AppState {
+        config_path: Arc::new(PathBuf::from("monitor.toml")),
+        watchlist_tx: Arc::new(watch::channel(vec![ticker_config("QQQ")]).0),
+        database,
+    }
+}
@@ -0,0 +1,70 @@
+use chrono::DateTime;
+use reqwest::Client;
+
+use crate::{
+    market_data::fetch_error::{parse_retry_after, FetchError},
+    polygon_api::stock_data_response::PriceDatum,
+    twelve_data_api::time_series_response::{to_interval, TimeSeriesResponse},
+};
+
+pub const TWELVE_DATA_BASE_URL: &str = "https://api.twelvedata.com";
+
+/**
+ * Fetches the candles for a symbol between timestamp_from and timestamp_to from Twelve Data's time_series endpoint
+ *
+ * Twelve Data defaults to the exchange time zone, so both the request and the response are pinned to UTC.
+ */
+pub async fn fetch_time_series(
+    client: &Client,
+    base_url: &str,
+    symbol: &str,
+    timeframe: u32,
+    timestamp_from: i64,
+    timestamp_to: i64,
+    api_key: &str,
+) -> Result<Vec<PriceDatum>, FetchError> {
+    let interval = to_interval(timeframe)?;
+
+    let res = client
+        .get(format!("{}/time_series", base_url))
+        .query(&[
+            ("symbol", symbol),
+            ("interval", interval),
+            ("start_date", &format_utc(timestamp_from)?),
+            ("end_date", &format_utc(timestamp_to)?),
+            ("timezone", "UTC"),
+            ("apikey", api_key),
+        ])
+        .send()
+        .await?;
+
+    let status = res.status();
+    let retry_after = parse_retry_after(res.headers());
+    let body = res.text().await?;
+
+    // The error envelope is returned for most failures, so try it before falling back to the status code
+    let price_data = match serde_json::from_str::<TimeSeriesResponse>(&body) {
+        Ok(time_series) => {
+            time_series.check_interval(interval)?;
+            time_series.into_price_data().map_err(|e| match FetchError::from(e) {
+                FetchError::RateLimited { .. } => FetchError::RateLimited { retry_after },
+                e => e,
+            })?
+        }
+        Err(_) if !status.is_success() => return Err(FetchError::from_status(status.as_u16(), body, retry_after)),
+        Err(e) => return Err(e.into()),
+    };
+
+    if price_data.is_empty() {
+        return Err(FetchError::EmptyResults);
+    }
+
+    Ok(price_data)
+}
+
+fn format_utc(timestamp_millis: i64) -> Result<String, FetchError> {
+    let datetime = DateTime::from_timestamp_millis(timestamp_millis)
+        .ok_or_else(|| FetchError::Unsupported(format!("{} is not a valid timestamp", timestamp_millis)))?;
+
+    Ok(datetime.format("%Y-%m-%d %H:%M:%S").to_string())
+}
@@ -0,0 +1,3 @@
+pub mod fetch_time_series;
+pub mod time_series_response;
+pub mod twelve_data_provider;
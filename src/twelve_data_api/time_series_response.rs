@@ -0,0 +1,193 @@
+use std::{error::Error, fmt};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Deserialize;
+
+use crate::{market_data::fetch_error::FetchError, polygon_api::stock_data_response::PriceDatum};
+
+/**
+ * Sample entry, numbers and datetimes are strings and values are newest first
+ * {
+ *   "meta": {"symbol": "QQQ", "interval": "15min", "exchange_timezone": "America/New_York", ...},
+ *   "values": [
+ *     {"datetime": "2025-07-14 14:00:00", "open": "553.10999", "high": "553.64001", "low": "552.73999", "close": "553.41998", "volume": "1843221"},
+ *     ...
+ *   ],
+ *   "status": "ok"
+ * }
+ *
+ * Errors come back in the same envelope, usually with a 200 status code
+ * {"code": 401, "message": "**apikey** parameter is incorrect ...", "status": "error"}
+ */
+#[derive(Deserialize, Debug)]
+pub struct TimeSeriesResponse {
+    pub status: String,
+    pub code: Option<u16>,
+    pub message: Option<String>,
+    pub meta: Option<TimeSeriesMeta>,
+    pub values: Option<Vec<TimeSeriesValue>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TimeSeriesMeta {
+    pub symbol: String,
+    pub interval: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TimeSeriesValue {
+    pub datetime: String,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    pub volume: Option<String>, // not reported for forex pairs
+}
+
+/**
+ * Ways a Twelve Data response can fail to become PriceDatum
+ *
+ * Api: the error envelope, status "error" with a code and message
+ * InvalidNumber: a price or volume string that does not parse
+ * InvalidDatetime: a datetime string in neither of the formats Twelve Data uses
+ * UnsupportedTimeframe: a candle size with no matching Twelve Data interval
+ * UnexpectedInterval: the meta block describes candles of another size than the ones asked for
+ */
+#[derive(Debug, PartialEq)]
+pub enum TwelveDataError {
+    Api { code: u16, message: String },
+    InvalidNumber { field: &'static str, value: String },
+    InvalidDatetime(String),
+    UnsupportedTimeframe(u32),
+    UnexpectedInterval { expected: String, symbol: String, interval: String },
+}
+
+impl fmt::Display for TwelveDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TwelveDataError::Api { code, message } => write!(f, "twelvedata error {}: {}", code, message),
+            TwelveDataError::InvalidNumber { field, value } => write!(f, "twelvedata returned a non numeric {}: {:?}", field, value),
+            TwelveDataError::InvalidDatetime(value) => write!(f, "twelvedata returned an unreadable datetime: {:?}", value),
+            TwelveDataError::UnsupportedTimeframe(timeframe) => write!(f, "twelvedata has no {} minute interval", timeframe),
+            TwelveDataError::UnexpectedInterval { expected, symbol, interval } => {
+                write!(f, "twelvedata returned {} {} candles instead of {}", symbol, interval, expected)
+            }
+        }
+    }
+}
+
+impl Error for TwelveDataError {}
+
+impl From<TwelveDataError> for FetchError {
+    fn from(e: TwelveDataError) -> Self {
+        match e {
+            TwelveDataError::Api { code, message } => FetchError::from_status(code, message, None),
+            TwelveDataError::UnsupportedTimeframe(_) => FetchError::Unsupported(e.to_string()),
+            TwelveDataError::InvalidNumber { .. }
+            | TwelveDataError::InvalidDatetime(_)
+            | TwelveDataError::UnexpectedInterval { .. } => FetchError::Decode(e.to_string()),
+        }
+    }
+}
+
+impl TimeSeriesResponse {
+    /**
+     * Makes sure the candles are the size that was asked for, so they are not stored under the wrong timeframe.
+     * Error envelopes carry no meta block and pass.
+     */
+    pub fn check_interval(&self, expected: &str) -> Result<(), TwelveDataError> {
+        match &self.meta {
+            Some(meta) if meta.interval != expected => Err(TwelveDataError::UnexpectedInterval {
+                expected: expected.to_string(),
+                symbol: meta.symbol.clone(),
+                interval: meta.interval.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /**
+     * Converts the response into PriceDatum, oldest first
+     *
+     * Datetimes are read as UTC, which is what fetch_time_series asks Twelve Data for.
+     */
+    pub fn into_price_data(self) -> Result<Vec<PriceDatum>, TwelveDataError> {
+        if self.status == "error" {
+            return Err(TwelveDataError::Api {
+                code: self.code.unwrap_or(0),
+                message: self.message.unwrap_or_default(),
+            });
+        }
+
+        let mut price_data = self
+            .values
+            .unwrap_or_default()
+            .iter()
+            .map(to_price_datum)
+            .collect::<Result<Vec<PriceDatum>, TwelveDataError>>()?;
+
+        price_data.sort_by_key(|price_datum| price_datum.timestamp);
+        Ok(price_data)
+    }
+}
+
+fn to_price_datum(value: &TimeSeriesValue) -> Result<PriceDatum, TwelveDataError> {
+    Ok(PriceDatum {
+        open: parse_price("open", &value.open)?,
+        high: parse_price("high", &value.high)?,
+        low: parse_price("low", &value.low)?,
+        close: parse_price("close", &value.close)?,
+        volume: match &value.volume {
+            Some(volume) => parse_volume(volume)?,
+            None => 0,
+        },
+        timestamp: parse_datetime_millis(&value.datetime)?,
+        vwap: None,
+        trade_count: None,
+    })
+}
+
+fn parse_price(field: &'static str, value: &str) -> Result<f64, TwelveDataError> {
+    value.trim().parse::<f64>().map_err(|_| TwelveDataError::InvalidNumber {
+        field,
+        value: value.to_string(),
+    })
+}
+
+// Volumes are integers for equities but can have a fractional part for crypto
+fn parse_volume(value: &str) -> Result<u64, TwelveDataError> {
+    let volume = parse_price("volume", value)?;
+    if volume < 0.0 {
+        return Err(TwelveDataError::InvalidNumber { field: "volume", value: value.to_string() });
+    }
+    Ok(volume.round() as u64)
+}
+
+/**
+ * Intraday candles look like "2025-07-14 13:30:00", daily and longer candles like "2025-07-14"
+ */
+fn parse_datetime_millis(value: &str) -> Result<u64, TwelveDataError> {
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|date| date.and_hms_opt(0, 0, 0).unwrap()))
+        .map_err(|_| TwelveDataError::InvalidDatetime(value.to_string()))?;
+
+    u64::try_from(naive.and_utc().timestamp_millis()).map_err(|_| TwelveDataError::InvalidDatetime(value.to_string()))
+}
+
+/**
+ * Twelve Data interval name for a candle size in minutes
+ */
+pub fn to_interval(timeframe: u32) -> Result<&'static str, TwelveDataError> {
+    match timeframe {
+        1 => Ok("1min"),
+        5 => Ok("5min"),
+        15 => Ok("15min"),
+        30 => Ok("30min"),
+        45 => Ok("45min"),
+        60 => Ok("1h"),
+        120 => Ok("2h"),
+        240 => Ok("4h"),
+        1440 => Ok("1day"),
+        _ => Err(TwelveDataError::UnsupportedTimeframe(timeframe)),
+    }
+}
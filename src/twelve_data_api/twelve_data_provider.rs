@@ -0,0 +1,184 @@
+use async_trait::async_trait;
+use reqwest::Client;
+
+use crate::{
+    market_data::{
+        asset_class::display_name,
+        fetch_error::FetchError,
+        market_data_provider::{MarketDataProvider, RateLimitHints},
+    },
+    polygon_api::stock_data_response::PriceDatum,
+    twelve_data_api::fetch_time_series::{fetch_time_series, TWELVE_DATA_BASE_URL},
+};
+
+/**
+ * Twelve Data time_series API as a MarketDataProvider
+ */
+pub struct TwelveDataProvider {
+    api_key: String,
+    base_url: String,
+    client: Client,
+}
+
+impl TwelveDataProvider {
+    pub fn new(api_key: String) -> Self {
+        Self::with_base_url(api_key, TWELVE_DATA_BASE_URL.to_string())
+    }
+
+    /**
+     * Points the provider at another host, used to run against a local mock server
+     */
+    pub fn with_base_url(api_key: String, base_url: String) -> Self {
+        TwelveDataProvider {
+            api_key,
+            base_url,
+            client: Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl MarketDataProvider for TwelveDataProvider {
+    fn name(&self) -> &str {
+        "twelvedata"
+    }
+
+    // Basic plan: 8 requests per minute
+    fn rate_limit_hints(&self) -> RateLimitHints {
+        RateLimitHints {
+            requests_per_minute: 8,
+            burst: 8,
+        }
+    }
+
+    async fn fetch_bars(
+        &self,
+        ticker: &str,
+        timeframe: u32,
+        timestamp_from: i64,
+        timestamp_to: i64,
+    ) -> Result<Vec<PriceDatum>, FetchError> {
+        // Watchlist symbols are Polygon tickers, Twelve Data names pairs like BTC/USD
+        let symbol = display_name(ticker);
+        fetch_time_series(&self.client, &self.base_url, &symbol, timeframe, timestamp_from, timestamp_to, &self.api_key).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use axum::{extract::Query, routing::get, Router};
+
+    use super::*;
+
+    const QQQ_15MIN: &str = include_str!("fixtures/time_series_qqq_15min.json");
+    const INVALID_API_KEY: &str = include_str!("fixtures/time_series_error_invalid_api_key.json");
+    const FX_NO_VOLUME: &str = include_str!("fixtures/time_series_fx_no_volume.json");
+
+    /**
+     * Serves a recorded fixture from /time_series on a random local port,
+     * after checking the request carries the parameters we rely on
+     */
+    async fn mock_twelve_data(fixture: &'static str) -> String {
+        let app = Router::new().route(
+            "/time_series",
+            get(move |Query(params): Query<HashMap<String, String>>| async move {
+                assert_eq!(params.get("timezone").map(String::as_str), Some("UTC"));
+                assert!(params.contains_key("interval"));
+                assert!(params.contains_key("start_date"));
+                assert!(params.contains_key("end_date"));
+                fixture
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn maps_time_series_to_price_data_oldest_first() {
+        let provider = TwelveDataProvider::with_base_url("test".to_string(), mock_twelve_data(QQQ_15MIN).await);
+
+        let bars = provider.fetch_bars("QQQ", 15, 1752499800000, 1752501600000).await.unwrap();
+
+        assert_eq!(bars.len(), 3);
+        assert_eq!(
+            bars[0],
+            PriceDatum {
+                open: 552.07001,
+                high: 552.90002,
+                low: 551.53003,
+                close: 552.38,
+                volume: 4715290,
+                timestamp: 1752499800000,
+                vwap: None,
+                trade_count: None,
+            }
+        );
+        assert_eq!(bars[1].timestamp, 1752500700000);
+        assert_eq!(bars[2].timestamp, 1752501600000);
+        assert_eq!(bars[2].close, 553.41998);
+    }
+
+    #[tokio::test]
+    async fn latest_bar_is_the_newest_value() {
+        let provider = TwelveDataProvider::with_base_url("test".to_string(), mock_twelve_data(QQQ_15MIN).await);
+
+        let bar = provider.fetch_latest_bar("QQQ", 15, 1752501600000).await.unwrap();
+
+        assert_eq!(bar.timestamp, 1752501600000);
+        assert_eq!(bar.volume, 1843221);
+    }
+
+    #[tokio::test]
+    async fn surfaces_the_error_envelope() {
+        let provider = TwelveDataProvider::with_base_url("bad key".to_string(), mock_twelve_data(INVALID_API_KEY).await);
+
+        let err = provider.fetch_bars("QQQ", 15, 1752499800000, 1752501600000).await.unwrap_err();
+
+        match err {
+            FetchError::AuthFailure { status, message } => {
+                assert_eq!(status, 401);
+                assert!(message.contains("apikey"));
+            }
+            other => panic!("expected an auth failure, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_date_only_candles_without_volume() {
+        let provider = TwelveDataProvider::with_base_url("test".to_string(), mock_twelve_data(FX_NO_VOLUME).await);
+
+        let bars = provider.fetch_bars("EUR/USD", 1440, 1752451200000, 1752451200000).await.unwrap();
+
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].timestamp, 1752451200000);
+        assert_eq!(bars[0].volume, 0);
+        assert_eq!(bars[0].close, 1.1662);
+    }
+
+    #[tokio::test]
+    async fn rejects_candles_of_another_interval() {
+        let provider = TwelveDataProvider::with_base_url("test".to_string(), mock_twelve_data(QQQ_15MIN).await);
+
+        let err = provider.fetch_bars("QQQ", 30, 1752499800000, 1752501600000).await.unwrap_err();
+
+        match err {
+            FetchError::Decode(message) => assert_eq!(message, "twelvedata returned QQQ 15min candles instead of 30min"),
+            other => panic!("expected a decode error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_timeframes_without_an_interval() {
+        let provider = TwelveDataProvider::with_base_url("test".to_string(), mock_twelve_data(QQQ_15MIN).await);
+
+        let err = provider.fetch_bars("QQQ", 10, 1752499800000, 1752501600000).await.unwrap_err();
+
+        assert!(matches!(err, FetchError::Unsupported(_)), "expected unsupported, got {:?}", err);
+    }
+}